@@ -0,0 +1,57 @@
+use crate::algorithms::TradeTerms;
+use crate::primitives::quantities::Energy;
+use crate::primitives::web2::{Bid, Offer};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Energy left to the bids, the offers and the bid requirements while a market is matched
+#[derive(Default)]
+pub(crate) struct AvailableEnergy(HashMap<String, Energy>);
+
+impl AvailableEnergy {
+    /// Selects the energy of a match under the given terms and consumes it from the bid,
+    /// the offer and the satisfied requirement. Returns None if there is nothing to trade.
+    pub(crate) fn consume(&mut self, bid: &Bid, offer: &Offer, trade_terms: &TradeTerms) -> Option<Energy> {
+        let offer_energy = *self.0.entry(offer.id.clone()).or_insert(offer.energy);
+        let bid_energy = *self.0.entry(bid.id.clone()).or_insert(bid.energy);
+
+        let mut selected_energy = offer_energy.min(bid_energy);
+
+        let requirement_key = trade_terms.requirement_key(bid);
+        let mut requirement_energy = None;
+        if let (Some(key), Some(energy)) = (&requirement_key, trade_terms.requirement_energy) {
+            let energy = *self.0.entry(key.clone()).or_insert(energy);
+            selected_energy = selected_energy.min(energy);
+            requirement_energy = Some(energy);
+        }
+
+        if selected_energy.is_zero() {
+            return None;
+        }
+
+        let mut consumed_energy = vec![(bid.id.clone(), bid_energy), (offer.id.clone(), offer_energy)];
+        if let (Some(key), Some(energy)) = (requirement_key, requirement_energy) {
+            consumed_energy.push((key, energy));
+        }
+        let residual_energy: Option<Vec<(String, Energy)>> = consumed_energy
+            .into_iter()
+            .map(|(key, energy)| energy.checked_sub(selected_energy).map(|residual| (key, residual)))
+            .collect();
+        match residual_energy {
+            Some(residual_energy) => {
+                self.0.extend(residual_energy);
+                Some(selected_energy)
+            }
+            None => {
+                warn!(bid = %bid.id, offer = %offer.id, %selected_energy,
+                      "Selected energy exceeds the available energy, skipping the match");
+                None
+            }
+        }
+    }
+
+    /// Whether the offer has been matched in full
+    pub(crate) fn is_exhausted(&self, offer: &Offer) -> bool {
+        matches!(self.0.get(offer.id.as_str()), Some(energy) if energy.is_zero())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::algorithms::{PayAsBid, PayAsClear};
+use crate::primitives::web2::{BidOfferMatch, MatchingData};
+use crate::primitives::web3;
+use crate::utils::MycoError;
+use std::sync::Arc;
+
+/// Names of the matching algorithms that can be selected at runtime
+pub const ALGORITHMS: &[&str] = &[PayAsBidAlgorithm::NAME, PayAsClearAlgorithm::NAME];
+
+/// Common interface of the matching algorithms, so that the connectors
+/// do not depend on a specific matcher
+pub trait MatchingAlgorithm: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn r#match(&self, matching_data: &MatchingData) -> Vec<BidOfferMatch>;
+
+    /// Runs the algorithm on the web3 orders of a market, through their web2 representation
+    fn match_web3(&self, matching_data: &web3::MatchingData) -> Vec<web3::Trade> {
+        matching_data.to_trades(&self.r#match(&matching_data.to_web2()))
+    }
+}
+
+/// Looks up a matching algorithm by its name
+pub fn get_algorithm(name: &str) -> Result<Arc<dyn MatchingAlgorithm>, MycoError> {
+    match name {
+        PayAsBidAlgorithm::NAME => Ok(Arc::new(PayAsBidAlgorithm)),
+        PayAsClearAlgorithm::NAME => Ok(Arc::new(PayAsClearAlgorithm)),
+        _ => Err(MycoError::UnknownAlgorithm(name.to_string())),
+    }
+}
+
+/// Pay as bid: every trade is priced at the bid energy rate
+pub struct PayAsBidAlgorithm;
+
+impl PayAsBidAlgorithm {
+    pub const NAME: &'static str = "pay_as_bid";
+}
+
+impl MatchingAlgorithm for PayAsBidAlgorithm {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn r#match(&self, matching_data: &MatchingData) -> Vec<BidOfferMatch> {
+        matching_data.clone().pay_as_bid()
+    }
+}
+
+/// Pay as clear: every trade is priced at the uniform clearing rate of the market
+pub struct PayAsClearAlgorithm;
+
+impl PayAsClearAlgorithm {
+    pub const NAME: &'static str = "pay_as_clear";
+}
+
+impl MatchingAlgorithm for PayAsClearAlgorithm {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn r#match(&self, matching_data: &MatchingData) -> Vec<BidOfferMatch> {
+        matching_data.clone().pay_as_clear()
+    }
+}
@@ -1,2 +1,13 @@
+mod available_energy;
+mod matching_algorithm;
 mod pay_as_bid;
-pub use pay_as_bid::PayAsBid;
\ No newline at end of file
+mod pay_as_clear;
+mod requirements;
+mod verifier;
+pub use matching_algorithm::{
+    get_algorithm, MatchingAlgorithm, PayAsBidAlgorithm, PayAsClearAlgorithm, ALGORITHMS,
+};
+pub use pay_as_bid::PayAsBid;
+pub use pay_as_clear::PayAsClear;
+pub use requirements::{trade_terms, TradeTerms};
+pub use verifier::{MatchVerifier, RejectionReason, VerifiedMatch};
\ No newline at end of file
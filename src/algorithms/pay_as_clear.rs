@@ -0,0 +1,86 @@
+use crate::algorithms::available_energy::AvailableEnergy;
+use crate::algorithms::requirements::trade_terms;
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::web2::{Bid, BidOfferMatch, MatchingData, Offer};
+
+pub trait PayAsClear {
+    fn pay_as_clear(&mut self) -> Vec<BidOfferMatch>;
+    fn clearing_rate(&mut self) -> Option<EnergyRate>;
+}
+
+impl PayAsClear for MatchingData {
+    fn clearing_rate(&mut self) -> Option<EnergyRate> {
+        // Walk the merit order (bids from the most to the least expensive, offers from the
+        // cheapest to the most expensive) over the pairs that can trade. The clearing rate
+        // is the rate of the marginal offer, the most expensive one that gets matched.
+        self.bids.sort_by(|a, b| b.energy_rate.cmp(&a.energy_rate));
+        self.offers.sort_by(|a, b| a.energy_rate.cmp(&b.energy_rate));
+
+        merit_order_matches(self, None)
+            .iter()
+            .map(|(_, offer, _)| offer.energy_rate)
+            .max()
+    }
+
+    fn pay_as_clear(&mut self) -> Vec<BidOfferMatch> {
+        let clearing_rate = match self.clearing_rate() {
+            Some(clearing_rate) => clearing_rate,
+            None => return Vec::new(),
+        };
+
+        merit_order_matches(self, Some(clearing_rate))
+            .into_iter()
+            .map(|(bid, offer, selected_energy)| BidOfferMatch {
+                market_id: self.market_id.clone(),
+                time_slot: offer.time_slot,
+                bid,
+                selected_energy,
+                trade_rate: clearing_rate,
+                offer,
+            })
+            .collect()
+    }
+}
+
+/// Matches the sorted offers with the sorted bids they can trade with. Given a clearing
+/// rate, only offers at most at that rate and bids paying at least that rate are matched.
+fn merit_order_matches(
+    matching_data: &MatchingData,
+    clearing_rate: Option<EnergyRate>,
+) -> Vec<(Bid, Offer, Energy)> {
+    let mut matches = Vec::new();
+
+    let mut available_energy = AvailableEnergy::default();
+    for offer in &matching_data.offers {
+        if matches!(clearing_rate, Some(clearing_rate) if offer.energy_rate > clearing_rate) {
+            break;
+        }
+        for bid in &matching_data.bids {
+            if offer.seller == bid.buyer {
+                continue;
+            }
+
+            // Bids can only be matched if the rate they pay under their
+            // requirements is at least the clearing rate
+            let trade_terms = match trade_terms(bid, offer) {
+                Some(trade_terms) => trade_terms,
+                None => continue,
+            };
+            if matches!(clearing_rate, Some(clearing_rate) if clearing_rate > trade_terms.energy_rate) {
+                continue;
+            }
+
+            let selected_energy = match available_energy.consume(bid, offer, &trade_terms) {
+                Some(selected_energy) => selected_energy,
+                None => continue,
+            };
+
+            matches.push((bid.clone(), offer.clone(), selected_energy));
+
+            if available_energy.is_exhausted(offer) {
+                break;
+            }
+        }
+    }
+    matches
+}
@@ -0,0 +1,69 @@
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::web2::{Bid, Offer};
+
+/// Terms under which a bid can be matched with an offer
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeTerms {
+    /// Rate the buyer pays for the trade
+    pub energy_rate: EnergyRate,
+    /// Index of the bid requirement satisfied by the offer, if the bid has requirements
+    pub requirement_index: Option<usize>,
+    /// Maximum energy the buyer wants to buy under the satisfied requirement
+    pub requirement_energy: Option<Energy>,
+}
+
+impl TradeTerms {
+    /// Key used to track the energy left under the satisfied bid requirement
+    pub fn requirement_key(&self, bid: &Bid) -> Option<String> {
+        self.requirement_index
+            .map(|index| format!("{}/requirements/{}", bid.id, index))
+    }
+}
+
+/// Checks the requirements and attributes of both orders and returns the terms of
+/// the trade, or None if the bid and the offer cannot be matched
+pub fn trade_terms(bid: &Bid, offer: &Offer) -> Option<TradeTerms> {
+    let buyer = [bid.buyer.as_str(), bid.buyer_id.as_str()];
+    let seller = [offer.seller.as_str(), offer.seller_id.as_str()];
+
+    if let Some(offer_requirements) = &offer.requirements {
+        if !offer_requirements.is_empty()
+            && !offer_requirements.iter().any(|requirement| requirement.accepts_partner(&buyer))
+        {
+            return None;
+        }
+    }
+
+    let bid_requirements = match &bid.requirements {
+        Some(bid_requirements) if !bid_requirements.is_empty() => bid_requirements,
+        _ => {
+            if offer.energy_rate > bid.energy_rate {
+                return None;
+            }
+            return Some(TradeTerms {
+                energy_rate: bid.energy_rate,
+                requirement_index: None,
+                requirement_energy: None,
+            });
+        }
+    };
+
+    // The first requirement satisfied by the offer determines the terms of the trade
+    bid_requirements
+        .iter()
+        .enumerate()
+        .find_map(|(index, requirement)| {
+            let energy_rate = requirement.energy_rate.unwrap_or(bid.energy_rate);
+            if !requirement.accepts_partner(&seller)
+                || !requirement.accepts_energy_type(offer.attributes.as_ref())
+                || offer.energy_rate > energy_rate
+            {
+                return None;
+            }
+            Some(TradeTerms {
+                energy_rate,
+                requirement_index: Some(index),
+                requirement_energy: requirement.energy,
+            })
+        })
+}
@@ -0,0 +1,170 @@
+use crate::algorithms::trade_terms;
+use crate::primitives::quantities::Energy;
+use crate::primitives::web2::{Bid, BidOfferMatch, Offer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Reason why a recommended match was rejected by the verifier
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    UnknownBid,
+    UnknownOffer,
+    MarketMismatch,
+    TimeSlotMismatch,
+    OfferRateAboveBidRate,
+    RequirementsNotSatisfied,
+    TradeRateOutOfRange,
+    ZeroSelectedEnergy,
+    BidEnergyExceeded,
+    OfferEnergyExceeded,
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            RejectionReason::UnknownBid => "the bid is not known",
+            RejectionReason::UnknownOffer => "the offer is not known",
+            RejectionReason::MarketMismatch => "the orders do not belong to the market of the match",
+            RejectionReason::TimeSlotMismatch => {
+                "the orders do not belong to the time slot of the match"
+            }
+            RejectionReason::OfferRateAboveBidRate => "the offer rate is above the bid rate",
+            RejectionReason::RequirementsNotSatisfied => {
+                "the offer does not satisfy the bid requirements"
+            }
+            RejectionReason::TradeRateOutOfRange => {
+                "the trade rate is not between the offer and the bid rates"
+            }
+            RejectionReason::ZeroSelectedEnergy => "the selected energy is zero",
+            RejectionReason::BidEnergyExceeded => "the selected energy exceeds the bid energy",
+            RejectionReason::OfferEnergyExceeded => "the selected energy exceeds the offer energy",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Recommended match with the outcome of its verification
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct VerifiedMatch {
+    pub recommendation: BidOfferMatch,
+    pub rejection_reason: Option<RejectionReason>,
+}
+
+impl VerifiedMatch {
+    pub fn is_accepted(&self) -> bool {
+        self.rejection_reason.is_none()
+    }
+}
+
+/// Verifies recommended matches against the latest known orders of each market
+#[derive(Clone, Debug, Default)]
+pub struct MatchVerifier {
+    bids: HashMap<String, (String, Bid)>,
+    offers: HashMap<String, (String, Offer)>,
+}
+
+impl MatchVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets all the known orders, e.g. before a new orderbook snapshot is received
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.offers.clear();
+    }
+
+    /// Records the open orders of a market
+    pub fn update_market(&mut self, market_id: &str, bids: &[Bid], offers: &[Offer]) {
+        for bid in bids {
+            self.bids.insert(bid.id.clone(), (market_id.to_string(), bid.clone()));
+        }
+        for offer in offers {
+            self.offers.insert(offer.id.clone(), (market_id.to_string(), offer.clone()));
+        }
+    }
+
+    /// Verifies the recommendations in order. Only the accepted matches consume
+    /// the energy of their orders.
+    pub fn verify(&self, recommendations: &[BidOfferMatch]) -> Vec<VerifiedMatch> {
+        let mut consumed_energy: HashMap<String, Energy> = HashMap::new();
+        recommendations
+            .iter()
+            .map(|recommendation| {
+                let rejection_reason = self
+                    .check(recommendation, &consumed_energy)
+                    .err();
+                if rejection_reason.is_none() {
+                    for id in [&recommendation.bid.id, &recommendation.offer.id] {
+                        let consumed = consumed_energy.entry(id.clone()).or_default();
+                        *consumed = consumed.saturating_add(recommendation.selected_energy);
+                    }
+                }
+                VerifiedMatch {
+                    recommendation: recommendation.clone(),
+                    rejection_reason,
+                }
+            })
+            .collect()
+    }
+
+    fn check(
+        &self,
+        recommendation: &BidOfferMatch,
+        consumed_energy: &HashMap<String, Energy>,
+    ) -> Result<(), RejectionReason> {
+        let (bid_market_id, bid) = self
+            .bids
+            .get(&recommendation.bid.id)
+            .ok_or(RejectionReason::UnknownBid)?;
+        let (offer_market_id, offer) = self
+            .offers
+            .get(&recommendation.offer.id)
+            .ok_or(RejectionReason::UnknownOffer)?;
+
+        if *bid_market_id != recommendation.market_id
+            || *offer_market_id != recommendation.market_id
+        {
+            return Err(RejectionReason::MarketMismatch);
+        }
+        if bid.time_slot != recommendation.time_slot || offer.time_slot != recommendation.time_slot
+        {
+            return Err(RejectionReason::TimeSlotMismatch);
+        }
+
+        // The rate the buyer pays depends on the requirement satisfied by the offer
+        let trade_terms = match trade_terms(bid, offer) {
+            Some(trade_terms) => trade_terms,
+            None if offer.energy_rate > bid.energy_rate => {
+                return Err(RejectionReason::OfferRateAboveBidRate)
+            }
+            None => return Err(RejectionReason::RequirementsNotSatisfied),
+        };
+        if recommendation.trade_rate < offer.energy_rate
+            || recommendation.trade_rate > trade_terms.energy_rate
+        {
+            return Err(RejectionReason::TradeRateOutOfRange);
+        }
+
+        if recommendation.selected_energy.is_zero() {
+            return Err(RejectionReason::ZeroSelectedEnergy);
+        }
+        let exceeds = |id: &str, energy: Energy| {
+            consumed_energy
+                .get(id)
+                .copied()
+                .unwrap_or_default()
+                .checked_add(recommendation.selected_energy)
+                .map_or(true, |total| total > energy)
+        };
+        if exceeds(&bid.id, bid.energy) {
+            return Err(RejectionReason::BidEnergyExceeded);
+        }
+        if exceeds(&offer.id, offer.energy) {
+            return Err(RejectionReason::OfferEnergyExceeded);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// When the Web3 client starts a matching cycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchingCadence {
+    /// Every N finalized blocks, e.g. "blocks:4"
+    EveryBlocks(u32),
+    /// On a wall-clock interval, e.g. "interval:30s" or "interval:500ms"
+    Interval(Duration),
+    /// When a finalized block contains a new order, i.e. "new-order"
+    NewOrder,
+}
+
+impl Default for MatchingCadence {
+    fn default() -> Self {
+        MatchingCadence::EveryBlocks(4)
+    }
+}
+
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Some(millis) = value.strip_suffix("ms") {
+        millis.trim().parse().ok().map(Duration::from_millis)
+    } else if let Some(minutes) = value.strip_suffix('m') {
+        minutes.trim().parse::<u64>().ok()?.checked_mul(60).map(Duration::from_secs)
+    } else {
+        value.trim_end_matches('s').trim().parse().ok().map(Duration::from_secs)
+    }
+}
+
+impl FromStr for MatchingCadence {
+    type Err = Error;
+
+    fn from_str(cadence: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = cadence.split_once(':').unwrap_or((cadence, ""));
+        match kind.trim() {
+            "blocks" => match value.trim().parse::<u32>() {
+                Ok(blocks) if blocks > 0 => Ok(MatchingCadence::EveryBlocks(blocks)),
+                _ => Err(anyhow!("Invalid number of blocks {} in matching cadence", value)),
+            },
+            "interval" => match parse_duration(value) {
+                Some(interval) if !interval.is_zero() => Ok(MatchingCadence::Interval(interval)),
+                _ => Err(anyhow!("Invalid interval {} in matching cadence", value)),
+            },
+            "new-order" if value.trim().is_empty() => Ok(MatchingCadence::NewOrder),
+            _ => Err(anyhow!(
+                "Unknown matching cadence {}, expected blocks:<n>, interval:<duration> or new-order",
+                cadence
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MatchingCadence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchingCadence::EveryBlocks(blocks) => write!(f, "blocks:{}", blocks),
+            MatchingCadence::Interval(interval) => write!(f, "interval:{}ms", interval.as_millis()),
+            MatchingCadence::NewOrder => write!(f, "new-order"),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+/// Prefix shared by all the channels of the external Myco connection
+pub const CHANNEL_PREFIX: &str = "external-myco";
+
+/// Redis channels used to exchange messages with gsy-e. Each channel name
+/// embeds the identifier of the simulation (or market) it belongs to:
+/// `external-myco/<id>/<suffix>`, where the id can be empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    OffersBids,
+    OffersBidsResponse,
+    Recommendations,
+    RecommendationsVerification,
+    Events,
+}
+
+impl Channel {
+    const ALL: [Channel; 5] = [
+        Channel::OffersBids,
+        Channel::OffersBidsResponse,
+        Channel::Recommendations,
+        Channel::RecommendationsVerification,
+        Channel::Events,
+    ];
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Channel::OffersBids => "offers-bids/",
+            Channel::OffersBidsResponse => "offers-bids/response/",
+            Channel::Recommendations => "recommendations/",
+            Channel::RecommendationsVerification => "recommendations/verification/",
+            Channel::Events => "events/",
+        }
+    }
+
+    /// Name of the channel for the given simulation id
+    pub fn name(&self, simulation_id: &str) -> String {
+        format!("{}/{}/{}", CHANNEL_PREFIX, simulation_id, self.suffix())
+    }
+
+    /// Pattern matching the channel of every simulation
+    pub fn pattern(&self) -> String {
+        self.name("*")
+    }
+
+    /// Splits a channel name into the simulation id and the channel. The id can
+    /// contain separators, so the channel is recognised from the end of the name.
+    pub fn parse(channel_name: &str) -> Option<(String, Channel)> {
+        let rest = channel_name.strip_prefix(CHANNEL_PREFIX)?.strip_prefix('/')?;
+        let rest = rest.trim_end_matches('/');
+        Channel::ALL.iter().find_map(|channel| {
+            let simulation_id = rest
+                .strip_suffix(channel.suffix().trim_end_matches('/'))?
+                .strip_suffix('/')?;
+            Some((simulation_id.to_string(), *channel))
+        })
+    }
+}
@@ -1,4 +1,20 @@
+mod cadence;
+mod channels;
 mod redis_connector;
+mod shutdown;
+mod signer;
 mod substrate_connector;
-pub use redis_connector::redis_subscribe;
-pub use substrate_connector::substrate_subscribe;
\ No newline at end of file
+mod supervisor;
+mod trigger_policy;
+pub use cadence::MatchingCadence;
+pub use channels::{Channel, CHANNEL_PREFIX};
+pub use redis_connector::{match_offers_bids, redis_subscribe};
+pub use shutdown::{Shutdown, ShutdownSignal};
+pub use signer::{load_signer, KeyScheme, MycoSigner};
+pub use substrate_connector::{
+    check_admin_call, check_myco_operator, delete_orders, delete_orders_by_proxy, fetch_orders,
+    gsy_node, insert_orders_into_orderbook, submit_admin_call, submit_orders, substrate_subscribe,
+    AdminCall, OrderEvent,
+};
+pub use supervisor::{ReconnectSupervisor, RetryPolicy};
+pub use trigger_policy::{TickTrigger, TickTriggers, TriggerPolicy};
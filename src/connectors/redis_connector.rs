@@ -1,180 +1,331 @@
-use crate::algorithms::PayAsBid;
-use crate::primitives::web2::{Bid, BidOfferMatch, MatchingData, Offer};
-
-use anyhow::{Error, Result};
-use serde_json::{Value, json};
-use chrono::{NaiveDateTime};
-use redis::Commands;
-
-pub fn value_to_str(value: &Value) -> String {
-    // Helper function to convert the serde Value to String
-    match value.as_str() {
-        Some(..) => value.as_str().unwrap().to_string(),
-        None => String::new(),
-    }
-}
+use crate::algorithms::{MatchVerifier, MatchingAlgorithm};
+use crate::connectors::{Channel, ReconnectSupervisor, RetryPolicy, Shutdown, TickTriggers, TriggerPolicy};
+use crate::primitives::messages::{
+    parse_message, OffersBidsResponse, RecommendationsMessage, RejectedMatch, TickEvent,
+    TimeSlotOrders, VerificationResponse,
+};
+use crate::primitives::web2::{BidOfferMatch, MatchingData};
+use crate::utils::health::HEALTH;
+use crate::utils::{metrics, MycoError};
 
-pub fn value_to_f32(value: &Value) -> f32 {
-    // Helper function to convert the serde Value to f32
-    match value.as_f64() {
-        Some(..) => value.as_f64().unwrap() as f32,
-        None => 0 as f32,
-    }
-}
+use futures::StreamExt;
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
-pub fn value_to_datetime(value: &Value) -> Option<NaiveDateTime> {
-    // Helper function to convert the serde Value to NaiveDateTime
-    match value.as_str() {
-        Some(..) => match NaiveDateTime::parse_from_str(value.as_str().unwrap(), "%Y-%m-%dT%H:%M:%S") {
-            Ok(datetime) => Some(datetime),
-            Err(_e) => None,
-        },
-        None => None,
-    }
+/// State kept for each simulation connected through Redis
+struct SimulationState {
+    verifier: MatchVerifier,
+    triggers: TickTriggers,
 }
 
-pub fn read_bids(orders: &Value) -> Vec<Bid> {
-    // Create an array of Bid structs from the serde Value
-    let mut bids_list = Vec::new();
-    for bid in orders.as_array().unwrap() {
-        let bid_struct = Bid {
-            r#type: value_to_str(&bid["type"]),
-            id: value_to_str(&bid["id"]),
-            energy: value_to_f32(&bid["energy"]),
-            energy_rate: value_to_f32(&bid["energy_rate"]),
-            original_price: value_to_f32(&bid["original_price"]),
-            attributes: Some(value_to_str(&bid["attributes"])),
-            requirements: Some(value_to_str(&bid["requirements"])),
-            buyer_origin: value_to_str(&bid["buyer_origin"]),
-            buyer_origin_id: value_to_str(&bid["buyer_origin_id"]),
-            buyer_id: value_to_str(&bid["buyer_id"]),
-            buyer: value_to_str(&bid["buyer"]),
-            time_slot: value_to_datetime(&bid["time_slot"]),
-            creation_time: value_to_datetime(&bid["creation_time"]),
-        };
-        bids_list.push(bid_struct);
-    }
-    bids_list
+async fn publish<T: Serialize>(
+    publisher: &mut MultiplexedConnection,
+    channel: Channel,
+    simulation_id: &str,
+    message: &T,
+) -> Result<(), MycoError> {
+    let channel_name = channel.name(simulation_id);
+    let payload = serde_json::to_string(message)?;
+    publisher
+        .publish::<&str, String, redis::Value>(&channel_name, payload)
+        .await
+        .map_err(|source| MycoError::Publish {
+            channel: channel_name,
+            source,
+        })?;
+    Ok(())
 }
 
-pub fn read_offers(orders: &Value) -> Vec<Offer> {
-    // Create an array of Offers from the serde Value
-    let mut offers_list = Vec::new();
-    for offer in orders.as_array().unwrap() {
-        let offer_struct = Offer {
-            r#type: value_to_str(&offer["type"]),
-            id: value_to_str(&offer["id"]),
-            energy: value_to_f32(&offer["energy"]),
-            energy_rate: value_to_f32(&offer["energy_rate"]),
-            original_price: value_to_f32(&offer["original_price"]),
-            attributes: Some(value_to_str(&offer["attributes"])),
-            requirements: Some(value_to_str(&offer["requirements"])),
-            seller_origin: value_to_str(&offer["seller_origin"]),
-            seller_origin_id: value_to_str(&offer["seller_origin_id"]),
-            seller_id: value_to_str(&offer["seller_id"]),
-            seller: value_to_str(&offer["seller"]),
-            time_slot: value_to_datetime(&offer["time_slot"]),
-            creation_time: value_to_datetime(&offer["creation_time"]),
+pub fn process_market_id(
+    time_slots: &BTreeMap<String, TimeSlotOrders>,
+    market_id: &str,
+    algorithm: &dyn MatchingAlgorithm,
+    verifier: &mut MatchVerifier,
+) -> Vec<BidOfferMatch> {
+    let _market_span = info_span!("market", market_id).entered();
+    let mut matches = Vec::new();
+    // Create a MatchingData Struct for the selected matching algorithm
+    for (time_slot, orders) in time_slots.iter() {
+        debug!(time_slot = %time_slot, bids = orders.bids.len(), offers = orders.offers.len(), "Matching time slot");
+        verifier.update_market(market_id, &orders.bids, &orders.offers);
+        metrics::record_orders(market_id, orders.bids.len(), orders.offers.len());
+        let matching_data = MatchingData {
+            bids: orders.bids.clone(),
+            offers: orders.offers.clone(),
+            market_id: market_id.to_string(),
         };
-        offers_list.push(offer_struct);
+        let timer = metrics::MATCHING_DURATION.with_label_values(&[algorithm.name()]).start_timer();
+        let algorithm_result = algorithm.r#match(&matching_data);
+        timer.observe_duration();
+        metrics::record_matches(algorithm.name(), &algorithm_result);
+        matches.extend(algorithm_result)
     }
-    offers_list
+    info!(matches = matches.len(), "Market matched");
+    matches
 }
 
-pub fn process_market_id_for_pay_as_bid(obj: &Value, market_id: &str) -> Vec<BidOfferMatch> {
+/// Runs the algorithm on every market and time slot of an offers-bids response
+pub fn match_offers_bids(
+    response: &OffersBidsResponse,
+    algorithm: &dyn MatchingAlgorithm,
+    verifier: &mut MatchVerifier,
+) -> Vec<BidOfferMatch> {
     let mut matches = Vec::new();
-    // Create a MatchingData Struct for the pay as bid algorithm
-    for (_timestamp, obj) in obj.as_object().unwrap().iter() {
-        let mut bids_list = Vec::new();
-        let mut offers_list = Vec::new();
-        for (key, orders) in obj.as_object().unwrap().iter() {
-            if key == "bids" {
-                bids_list = read_bids(orders);
-            } else if key == "offers" {
-                offers_list = read_offers(orders);
-            } else {
-                panic!("Unable to process market id: key not in ['bids', 'offers', 'market_id'].")
-            }
-        }
-        let mut matching_data = MatchingData {
-            bids: bids_list,
-            offers: offers_list,
-            market_id: market_id.to_string(),
-        };
-        let algorithm_result = matching_data.pay_as_bid();
-        matches.extend(algorithm_result)
-        // TODO - add tests for the result
+    for (market_id, time_slots) in response.bids_offers.iter() {
+        matches.extend(process_market_id(time_slots, market_id, algorithm, verifier));
     }
     matches
 }
 
-pub fn unwrap_offers_bids_response(payload: &str, client: &redis::Client) {
+pub async fn unwrap_offers_bids_response(
+    simulation_id: &str,
+    payload: &str,
+    publisher: &mut MultiplexedConnection,
+    algorithm: &dyn MatchingAlgorithm,
+    verifier: &mut MatchVerifier,
+) -> Result<(), MycoError> {
     // When a message from the bids_offers channel is received,
-    // it extracts the market ids as keys to iterate over the
-    // corresponding sets of bids and offers and trigger the
-    // pay as bid algorithm.
-    let value: Value = serde_json::from_str(&payload).unwrap();
-    for (key, obj) in value.as_object().unwrap().iter() {
-        if key == "bids_offers" {
-            let mut matches = Vec::new();
-            for (_market_id, obj) in obj.as_object().unwrap().iter() {
-                matches.extend(process_market_id_for_pay_as_bid(obj, _market_id.as_str()));
-            }
+    // it iterates over the markets and their time slots to trigger the
+    // selected matching algorithm on each set of bids and offers.
+    // The orders are kept by the verifier as the latest known orders.
+    let response: OffersBidsResponse =
+        parse_message(&Channel::OffersBidsResponse.name(simulation_id), payload)?;
 
-            client.get_connection().unwrap().publish::<String, String, redis::Value>(
-                "external-myco//recommendations/".to_string(),
-                json!({"recommended_matches": matches}).to_string(),
-            ).expect("Cannot publish Redis message to recommendations channel.");
-        }
-    };
+    verifier.clear();
+    let matches = match_offers_bids(&response, algorithm, verifier);
+
+    publish(
+        publisher,
+        Channel::Recommendations,
+        simulation_id,
+        &RecommendationsMessage { recommended_matches: matches },
+    ).await
 }
 
-pub fn unwrap_recommendations_response(payload: &str) {
+pub async fn unwrap_recommendations_response(
+    simulation_id: &str,
+    payload: &str,
+    publisher: &mut MultiplexedConnection,
+    verifier: &MatchVerifier,
+) -> Result<(), MycoError> {
     // When a message from the recommendations channel is received,
-    // it is sent to the verifier function - TODO
+    // the recommended matches are checked against the latest known orders
+    // and the accepted and rejected matches are published back.
     // Will be sent to TradeSettlement pallet, rejected matches are sent back by the OCW - TODO
-    let _value: Value = serde_json::from_str(&payload).unwrap();
+    let channel_name = Channel::Recommendations.name(simulation_id);
+    let message: RecommendationsMessage = match parse_message(&channel_name, payload) {
+        Ok(message) => message,
+        Err(error) => {
+            // Let the matcher know that its recommendations were not processed
+            publish(publisher, Channel::RecommendationsVerification, simulation_id, &error.to_response()).await?;
+            return Err(error.into());
+        }
+    };
+
+    let mut response = VerificationResponse::default();
+    for verified_match in verifier.verify(&message.recommended_matches) {
+        if let Some(reason) = &verified_match.rejection_reason {
+            debug!(
+                bid = %verified_match.recommendation.bid.id,
+                offer = %verified_match.recommendation.offer.id,
+                %reason,
+                "Rejected match"
+            );
+        }
+        match verified_match.rejection_reason {
+            None => response.accepted_matches.push(verified_match.recommendation),
+            Some(reason) => response.rejected_matches.push(RejectedMatch {
+                recommendation: verified_match.recommendation,
+                description: reason.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    info!(
+        accepted = response.accepted_matches.len(),
+        rejected = response.rejected_matches.len(),
+        "Verified recommendations"
+    );
+    publish(publisher, Channel::RecommendationsVerification, simulation_id, &response).await
 }
 
-pub fn unwrap_tick_response(payload: &str, client: &redis::Client) {
+pub async fn unwrap_tick_response(
+    simulation_id: &str,
+    payload: &str,
+    publisher: &mut MultiplexedConnection,
+    triggers: &mut TickTriggers,
+) -> Result<(), MycoError> {
     // When a message from the tick channel is received,
-    // we check the slot completion %
-    let value: Value = serde_json::from_str(&payload).unwrap();
-    for (key, obj) in value.as_object().unwrap().iter() {
-        if key == "slot_completion" {
-            let slot_percent_str: &str = &obj.as_str().unwrap();
-            let length = slot_percent_str.len();
-            let slot_percent_int: i32 = slot_percent_str[..length - 1].parse().unwrap();
-            // TODO: change this fast fix with the proper logic
-            if slot_percent_int > 33 {
-                client.get_connection().unwrap().publish::<String, String, redis::Value>(
-                    "external-myco//offers-bids/".to_string(), "{}".to_string()
-                ).expect("Cannot publish Redis message to offers-bids channel.");
-            }
+    // the trigger policy of its market and slot decides from
+    // the slot completion % whether the open orders should be requested
+    let event: TickEvent = parse_message(&Channel::Events.name(simulation_id), payload)?;
+    if let Some(slot_completion) = event.slot_completion {
+        let market_id = event.market_id.as_deref();
+        let market_slot = event.market_slot.as_deref();
+        if triggers.on_tick(market_id, market_slot, slot_completion) {
+            debug!(
+                simulation_id,
+                market_id,
+                market_slot,
+                slot_completion = slot_completion.0,
+                "Requesting the open orders"
+            );
+            publish(publisher, Channel::OffersBids, simulation_id, &serde_json::json!({})).await?;
         }
     }
+    Ok(())
 }
 
-pub async fn redis_subscribe(channels: Vec<String>, url: String) -> Result<(), Error> {
-
-        let client = redis::Client::open(url)?;
+async fn listen(
+    client: &redis::Client,
+    channels: &[String],
+    algorithm: &dyn MatchingAlgorithm,
+    trigger_policy: &TriggerPolicy,
+    simulations: &mut HashMap<String, SimulationState>,
+    shutdown: &mut Shutdown,
+) -> Result<(), MycoError> {
+    // Subscribes to the channels and dispatches the messages until the
+    // connection to Redis is lost or the shutdown is requested. All the
+    // replies share one multiplexed connection and are published on the
+    // channels of the simulation the message came from. The messages are
+    // handled one at a time, so the one being handled when the shutdown
+    // is requested completes before the channels are unsubscribed.
+    let mut publisher = client.get_multiplexed_tokio_connection().await?;
+    let mut pubsub = client.get_async_connection().await?.into_pubsub();
+    for channel in channels {
+        pubsub.psubscribe(channel).await?;
+    }
+    info!(?channels, "Subscribed to Redis channels");
+    HEALTH.set_redis_subscribed(true);
 
-        let mut con = client.get_connection()?;
+    let mut messages = pubsub.on_message();
+    loop {
+        let msg = tokio::select! {
+            msg = messages.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = shutdown.requested() => break,
+        };
+        let payload: String = match msg.get_payload() {
+            Ok(payload) => payload,
+            Err(error) => {
+                error!(%error, "Unable to read Redis message");
+                continue;
+            }
+        };
+        let channel_name = msg.get_channel_name();
+        let (simulation_id, channel) = match Channel::parse(channel_name) {
+            Some(parsed_channel) => parsed_channel,
+            None => {
+                warn!(channel = channel_name, "Ignoring message on unknown channel");
+                continue;
+            }
+        };
+        let state = simulations
+            .entry(simulation_id.clone())
+            .or_insert_with(|| SimulationState {
+                verifier: MatchVerifier::new(),
+                triggers: TickTriggers::new(trigger_policy.clone()),
+            });
+        let result = match channel {
+            Channel::OffersBidsResponse => {
+                state.triggers.on_response();
+                let cycle_span =
+                    info_span!("matching_cycle", simulation_id = %simulation_id, algorithm = algorithm.name());
+                unwrap_offers_bids_response(
+                    &simulation_id,
+                    &payload,
+                    &mut publisher,
+                    algorithm,
+                    &mut state.verifier,
+                )
+                .instrument(cycle_span)
+                .await
+            }
+            Channel::Recommendations => {
+                let verification_span = info_span!("verification", simulation_id = %simulation_id);
+                unwrap_recommendations_response(&simulation_id, &payload, &mut publisher, &state.verifier)
+                    .instrument(verification_span)
+                    .await
+            }
+            Channel::Events => {
+                HEALTH.record_activity();
+                unwrap_tick_response(&simulation_id, &payload, &mut publisher, &mut state.triggers).await
+            }
+            Channel::OffersBids | Channel::RecommendationsVerification => {
+                debug!(channel = channel_name, "Ignoring message on outgoing channel");
+                continue;
+            }
+        };
+        if let Err(error) = result {
+            error!(channel = channel_name, %error, "Error while handling message");
+        }
+    }
+    drop(messages);
+    HEALTH.set_redis_subscribed(false);
 
-        let mut pubsub = con.as_pubsub();
+    if shutdown.is_requested() {
         for channel in channels {
-            pubsub.psubscribe(channel)?;
+            if let Err(error) = pubsub.punsubscribe(channel).await {
+                warn!(channel = channel.as_str(), %error, "Unable to unsubscribe from Redis channel");
+            }
         }
+        info!(?channels, "Unsubscribed from Redis channels");
+    }
+    Ok(())
+}
+
+pub async fn redis_subscribe(
+    channels: Vec<String>,
+    url: String,
+    algorithm: Arc<dyn MatchingAlgorithm>,
+    trigger_policy: TriggerPolicy,
+    retry_policy: RetryPolicy,
+    mut shutdown: Shutdown,
+) -> Result<(), MycoError> {
+    // Keeps the subscription alive: when Redis goes away, the client
+    // reconnects and subscribes again to all the channel patterns,
+    // until the supervisor gives up or the shutdown is requested.
+    let client = redis::Client::open(url)?;
+    let mut simulations = HashMap::new();
+    let mut supervisor = ReconnectSupervisor::new("Redis", retry_policy);
 
-        loop {
-            let msg = pubsub.get_message().unwrap();
-            let payload: String = msg.get_payload().unwrap();
-            let channel_name = msg.get_channel_name();
-            match channel_name {
-                "external-myco//offers-bids/response/" => unwrap_offers_bids_response(&payload, &client),
-                "external-myco//recommendations/" => unwrap_recommendations_response(&payload),
-                "external-myco//events/" => unwrap_tick_response(&payload, &client),
-                _ => unwrap_recommendations_response(&payload),
-            };
+    loop {
+        let session = listen(
+            &client,
+            &channels,
+            algorithm.as_ref(),
+            &trigger_policy,
+            &mut simulations,
+            &mut shutdown,
+        )
+        .await;
+        if shutdown.is_requested() {
+            break;
+        }
+        tokio::select! {
+            reconnect = supervisor.reconnect(session) => reconnect?,
+            _ = shutdown.requested() => break,
         }
-}
\ No newline at end of file
+    }
+
+    // The open orders requested from the simulations will not be matched
+    let pending: Vec<&String> = simulations
+        .iter()
+        .filter(|(_, state)| state.triggers.has_outstanding_request())
+        .map(|(simulation_id, _)| simulation_id)
+        .collect();
+    info!(
+        signal = ?shutdown.signal(),
+        simulations = simulations.len(),
+        ?pending,
+        "Redis connector shut down"
+    );
+    Ok(())
+}
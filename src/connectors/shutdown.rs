@@ -0,0 +1,97 @@
+use crate::utils::MycoError;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use tracing::{error, warn};
+
+/// Signal that asked the client to shut down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownSignal {
+    Interrupt,
+    Terminate,
+}
+
+impl ShutdownSignal {
+    /// 128 + the signal number, as a shell reports a process killed by it
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ShutdownSignal::Interrupt => 130,
+            ShutdownSignal::Terminate => 143,
+        }
+    }
+}
+
+impl Display for ShutdownSignal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownSignal::Interrupt => write!(f, "SIGINT"),
+            ShutdownSignal::Terminate => write!(f, "SIGTERM"),
+        }
+    }
+}
+
+/// Shutdown requested by SIGINT or SIGTERM. The connectors stop taking new
+/// ticks or blocks once it is requested, and wait for the work in flight up
+/// to the drain timeout. A second signal exits immediately.
+#[derive(Clone)]
+pub struct Shutdown {
+    receiver: watch::Receiver<Option<ShutdownSignal>>,
+    drain_timeout: Duration,
+}
+
+impl Shutdown {
+    /// Starts listening for SIGINT and SIGTERM
+    pub fn listen(drain_timeout: Duration) -> Result<Self, MycoError> {
+        let mut interrupt = signal(SignalKind::interrupt()).map_err(MycoError::Signal)?;
+        let mut terminate = signal(SignalKind::terminate()).map_err(MycoError::Signal)?;
+        let (sender, receiver) = watch::channel(None);
+        tokio::spawn(async move {
+            let mut requested = false;
+            loop {
+                let signal = tokio::select! {
+                    _ = interrupt.recv() => ShutdownSignal::Interrupt,
+                    _ = terminate.recv() => ShutdownSignal::Terminate,
+                };
+                if requested {
+                    error!(%signal, "Second signal received, exiting without draining");
+                    std::process::exit(signal.exit_code());
+                }
+                warn!(%signal, ?drain_timeout, "Shutting down, send the signal again to exit immediately");
+                requested = true;
+                // The receivers are never all dropped before the process exits
+                let _ = sender.send(Some(signal));
+            }
+        });
+        Ok(Self {
+            receiver,
+            drain_timeout,
+        })
+    }
+
+    pub fn signal(&self) -> Option<ShutdownSignal> {
+        *self.receiver.borrow()
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.signal().is_some()
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    /// Resolves once the shutdown is requested
+    pub async fn requested(&mut self) {
+        while self.receiver.borrow().is_none() {
+            if self.receiver.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Exit code of the client once it is shut down
+    pub fn exit_code(&self) -> i32 {
+        self.signal().map_or(0, |signal| signal.exit_code())
+    }
+}
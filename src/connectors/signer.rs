@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Error, Result};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use subxt::{
+    sp_core::{crypto::AccountId32, ed25519, sr25519, Pair},
+    Config, DefaultConfig, PairSigner, Signer,
+};
+
+/// Signature scheme of the Myco operator key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyScheme {
+    Sr25519,
+    Ed25519,
+}
+
+impl FromStr for KeyScheme {
+    type Err = Error;
+
+    fn from_str(scheme: &str) -> Result<Self, Self::Err> {
+        match scheme.to_lowercase().as_str() {
+            "sr25519" => Ok(KeyScheme::Sr25519),
+            "ed25519" => Ok(KeyScheme::Ed25519),
+            _ => Err(anyhow!("Unknown key scheme {}, expected sr25519 or ed25519", scheme)),
+        }
+    }
+}
+
+impl fmt::Display for KeyScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyScheme::Sr25519 => write!(f, "sr25519"),
+            KeyScheme::Ed25519 => write!(f, "ed25519"),
+        }
+    }
+}
+
+/// Signer of the extrinsics submitted by the Myco operator
+pub enum MycoSigner {
+    Sr25519(PairSigner<DefaultConfig, sr25519::Pair>),
+    Ed25519(PairSigner<DefaultConfig, ed25519::Pair>),
+}
+
+impl MycoSigner {
+    /// Creates the signer from a secret URI (e.g. "//Alice"), a mnemonic or a hex seed
+    pub fn from_secret_uri(secret_uri: &str, scheme: KeyScheme) -> Result<Self, Error> {
+        match scheme {
+            KeyScheme::Sr25519 => sr25519::Pair::from_string(secret_uri, None)
+                .map(|pair| MycoSigner::Sr25519(PairSigner::new(pair)))
+                .map_err(|error| anyhow!("Invalid sr25519 secret: {:?}", error)),
+            KeyScheme::Ed25519 => ed25519::Pair::from_string(secret_uri, None)
+                .map(|pair| MycoSigner::Ed25519(PairSigner::new(pair)))
+                .map_err(|error| anyhow!("Invalid ed25519 secret: {:?}", error)),
+        }
+    }
+
+    /// Creates the signer from a keystore file of the Substrate node, which
+    /// stores the secret phrase as a JSON string, or from a plain text file.
+    /// The encrypted JSON accounts exported by polkadot-js are not supported.
+    pub fn from_keystore(path: &Path, scheme: KeyScheme) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|error| anyhow!("Unable to read keystore {}: {}", path.display(), error))?;
+        if let Ok(serde_json::Value::Object(_)) = serde_json::from_str(&content) {
+            return Err(anyhow!(
+                "Keystore {} is a JSON object, only a secret phrase (as a JSON string or plain text) is \
+                 supported, encrypted polkadot-js exports are not",
+                path.display()
+            ));
+        }
+        let secret_uri = serde_json::from_str::<String>(&content)
+            .unwrap_or_else(|_| content.trim().to_string());
+        Self::from_secret_uri(&secret_uri, scheme)
+    }
+}
+
+impl Signer<DefaultConfig> for MycoSigner {
+    fn nonce(&self) -> Option<<DefaultConfig as Config>::Index> {
+        match self {
+            MycoSigner::Sr25519(signer) => signer.nonce(),
+            MycoSigner::Ed25519(signer) => signer.nonce(),
+        }
+    }
+
+    fn account_id(&self) -> &AccountId32 {
+        match self {
+            MycoSigner::Sr25519(signer) => signer.account_id(),
+            MycoSigner::Ed25519(signer) => signer.account_id(),
+        }
+    }
+
+    fn address(&self) -> <DefaultConfig as Config>::Address {
+        match self {
+            MycoSigner::Sr25519(signer) => signer.address(),
+            MycoSigner::Ed25519(signer) => signer.address(),
+        }
+    }
+
+    fn sign(&self, signer_payload: &[u8]) -> <DefaultConfig as Config>::Signature {
+        match self {
+            MycoSigner::Sr25519(signer) => signer.sign(signer_payload),
+            MycoSigner::Ed25519(signer) => signer.sign(signer_payload),
+        }
+    }
+}
+
+/// Loads the Myco operator key from a secret URI or a keystore file
+pub fn load_signer(
+    secret_uri: Option<&str>,
+    keystore: Option<&Path>,
+    scheme: KeyScheme,
+) -> Result<MycoSigner, Error> {
+    match (secret_uri, keystore) {
+        (Some(secret_uri), None) => MycoSigner::from_secret_uri(secret_uri, scheme),
+        (None, Some(keystore)) => MycoSigner::from_keystore(keystore, scheme),
+        (Some(_), Some(_)) => Err(anyhow!("Use either a signer secret or a keystore, not both")),
+        (None, None) => Err(anyhow!(
+            "No signer configured, use --signer (MYCO_SIGNER) or --keystore (MYCO_KEYSTORE)"
+        )),
+    }
+}
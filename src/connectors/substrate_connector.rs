@@ -1,151 +1,879 @@
-use crate::primitives::web3::{Bid, Offer, Order, OrderSchema, OrderStatus};
-use anyhow::{Error, Result};
-use async_recursion::async_recursion;
-use sp_keyring::AccountKeyring;
+use crate::algorithms::MatchingAlgorithm;
+use crate::connectors::{MatchingCadence, MycoSigner, ReconnectSupervisor, RetryPolicy, Shutdown};
+use crate::primitives::web3::{
+    settlement_market_id, Bid, MatchingData, Offer, Order, OrderComponent, OrderSchema, OrderStatus, Trade,
+};
+use crate::utils::health::HEALTH;
+use crate::utils::{metrics, MycoError};
+use codec::Encode;
+use std::fmt;
 use std::sync::{Arc, Mutex};
-use std::{thread, time};
 use subxt::{
     rpc::Subscription,
+    sp_core::{crypto::AccountId32, H256},
     sp_runtime::{generic::Header, traits::BlakeTwo256},
-    ClientBuilder, DefaultConfig, PairSigner, PolkadotExtrinsicParams, SubstrateExtrinsicParams,
+    ClientBuilder, DefaultConfig, PolkadotExtrinsicParams, Signer, SubstrateExtrinsicParams,
+    TransactionEvents,
 };
-use text_colorizer::*;
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 #[subxt::subxt(runtime_metadata_path = "metadata.scale")]
 pub mod gsy_node {}
 
-#[async_recursion]
-pub async fn substrate_subscribe(orderbook_url: String, node_url: String) -> Result<(), Error> {
-    eprintln!("{} {}", "Connecting to".green(), node_url.green().bold());
+use gsy_node::runtime_types::gsy_collateral::pallet::Call as CollateralCall;
+use gsy_node::runtime_types::gsy_primitives::orders as chain_orders;
+use gsy_node::runtime_types::gsy_primitives::trades as chain_trades;
+use gsy_node::runtime_types::node_gsy_runtime::Call as RuntimeCall;
+use gsy_node::runtime_types::pallet_sudo::pallet::Call as SudoCall;
+
+type NodeApi = gsy_node::RuntimeApi<DefaultConfig, PolkadotExtrinsicParams<DefaultConfig>>;
+
+type ChainOrder = chain_orders::Order<AccountId32, u64>;
+type ChainBid = chain_orders::Bid<AccountId32, u64>;
+type ChainOffer = chain_orders::Offer<AccountId32, u64>;
+type ChainBidOfferMatch = chain_trades::BidOfferMatch<AccountId32, u64>;
+
+/// Runs the matching cycles one at a time and keeps track of the stage of
+/// the running one, so that a shutdown can wait for it and report it
+#[derive(Clone, Default)]
+struct MatchingCycles {
+    running: Arc<tokio::sync::Mutex<()>>,
+    stage: Arc<Mutex<Option<String>>>,
+}
+
+impl MatchingCycles {
+    fn try_start(&self, trigger: &str) -> Option<tokio::sync::OwnedMutexGuard<()>> {
+        let cycle_guard = Arc::clone(&self.running).try_lock_owned().ok()?;
+        self.set_stage(Some(format!("started by {}", trigger)));
+        Some(cycle_guard)
+    }
+
+    fn set_stage(&self, stage: Option<String>) {
+        if let Ok(mut current_stage) = self.stage.lock() {
+            *current_stage = stage;
+        }
+    }
+
+    fn stage(&self) -> Option<String> {
+        self.stage.lock().ok().and_then(|stage| stage.clone())
+    }
+
+    /// Waits for the running cycle, returns false if it is still running after the timeout
+    async fn drain(&self, timeout: std::time::Duration) -> bool {
+        tokio::time::timeout(timeout, self.running.lock()).await.is_ok()
+    }
+}
 
+/// Ensures that the signer is registered as a Myco operator on the node
+pub async fn check_myco_operator(node_url: String, signer: &MycoSigner) -> Result<(), MycoError> {
     let api = ClientBuilder::new()
-        .set_url(node_url.clone())
+        .set_url(node_url)
+        .build()
+        .await?
+        .to_runtime_api::<gsy_node::RuntimeApi<DefaultConfig, SubstrateExtrinsicParams<DefaultConfig>>>();
+
+    let account_id = signer.account_id();
+    let registered_myco = api
+        .storage()
+        .gsy_collateral()
+        .registered_myco(account_id, None)
+        .await?;
+    // RegisteredMyco is a value query, an unregistered account reads as the zero hash
+    if registered_myco == H256::zero() {
+        return Err(MycoError::NotMycoOperator(account_id.to_string()));
+    }
+    info!(%account_id, "Registered Myco operator");
+    Ok(())
+}
+
+async fn connect_to_node(node_url: &str) -> Result<NodeApi, MycoError> {
+    Ok(ClientBuilder::new()
+        .set_url(node_url)
+        .build()
+        .await?
+        .to_runtime_api::<NodeApi>())
+}
+
+pub async fn substrate_subscribe(
+    orderbook_url: String,
+    node_url: String,
+    algorithm: Arc<dyn MatchingAlgorithm>,
+    signer: Arc<MycoSigner>,
+    cadence: MatchingCadence,
+    retry_policy: RetryPolicy,
+    mut shutdown: Shutdown,
+) -> Result<(), MycoError> {
+    // Keeps following the finalized blocks: when the subscription is
+    // dropped, the client reconnects to the node until the supervisor
+    // gives up or the shutdown is requested.
+    info!(algorithm = algorithm.name(), %cadence, "Matching configuration");
+    let mut supervisor = ReconnectSupervisor::new("node", retry_policy);
+    // Shared across the reconnections, so that cycles never overlap,
+    // even with a cycle started before a reconnection
+    let matching_cycles = MatchingCycles::default();
+
+    loop {
+        let session = follow_finalized_blocks(
+            &orderbook_url,
+            &node_url,
+            &algorithm,
+            &signer,
+            &cadence,
+            &matching_cycles,
+            &mut shutdown,
+        )
+        .await;
+        if shutdown.is_requested() {
+            break;
+        }
+        tokio::select! {
+            reconnect = supervisor.reconnect(session) => reconnect?,
+            _ = shutdown.requested() => break,
+        }
+    }
+
+    // A settlement interrupted while it is submitted may or may not be
+    // included, so the running cycle is given the drain timeout to complete
+    let pending = matching_cycles.stage();
+    if pending.is_some() {
+        info!(?pending, timeout = ?shutdown.drain_timeout(), "Waiting for the running matching cycle");
+    }
+    if matching_cycles.drain(shutdown.drain_timeout()).await {
+        info!(signal = ?shutdown.signal(), ?pending, "Node connector shut down");
+    } else {
+        error!(
+            signal = ?shutdown.signal(),
+            pending = ?matching_cycles.stage(),
+            "Node connector shut down with a matching cycle still running"
+        );
+    }
+    Ok(())
+}
+
+async fn follow_finalized_blocks(
+    orderbook_url: &str,
+    node_url: &str,
+    algorithm: &Arc<dyn MatchingAlgorithm>,
+    signer: &Arc<MycoSigner>,
+    cadence: &MatchingCadence,
+    matching_cycles: &MatchingCycles,
+    shutdown: &mut Shutdown,
+) -> Result<(), MycoError> {
+    info!(node_url, "Connecting to the node");
+
+    let api = ClientBuilder::new()
+        .set_url(node_url)
         .build()
         .await?
         .to_runtime_api::<gsy_node::RuntimeApi<DefaultConfig, SubstrateExtrinsicParams<DefaultConfig>>>();
 
     let mut gsy_blocks_events: Subscription<Header<u32, BlakeTwo256>> =
         api.client.rpc().subscribe_finalized_blocks().await?;
+    HEALTH.set_node_subscribed(true);
+    // Readiness also requires the orderbook service, probe it before the first cycle
+    HEALTH.set_orderbook_reachable(reqwest::get(orderbook_url).await.is_ok());
 
-    let orderbook_url = Arc::new(Mutex::new(orderbook_url));
-    let node_url = Arc::new(Mutex::new(node_url.clone()));
+    let mut interval = match cadence {
+        MatchingCadence::Interval(period) => Some(tokio::time::interval(*period)),
+        _ => None,
+    };
+    loop {
+        let trigger = tokio::select! {
+            block = gsy_blocks_events.next() => {
+                let block = match block {
+                    Some(Ok(block)) => block,
+                    Some(Err(error)) => {
+                        error!(%error, "Block subscription error");
+                        break;
+                    }
+                    None => break,
+                };
+                HEALTH.record_activity();
+                let block_span = info_span!("block", number = block.number, hash = ?block.hash());
+                debug!(parent: &block_span, "Block finalized");
+                match cadence {
+                    MatchingCadence::EveryBlocks(blocks) if block.number % *blocks == 0 => {
+                        Some(format!("block {}", block.number))
+                    }
+                    MatchingCadence::NewOrder => {
+                        match block_has_new_orders(&api, block.hash()).instrument(block_span.clone()).await {
+                            Ok(true) => Some(format!("new order in block {}", block.number)),
+                            Ok(false) => None,
+                            Err(error) => {
+                                error!(parent: &block_span, %error, "Unable to read the events of the block");
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ = next_interval_tick(&mut interval) => Some(String::from("interval")),
+            _ = shutdown.requested() => {
+                info!("No longer following the finalized blocks");
+                HEALTH.set_node_subscribed(false);
+                return Ok(());
+            }
+        };
+        let trigger = match trigger {
+            Some(trigger) => trigger,
+            None => continue,
+        };
 
-    while let Some(Ok(block)) = gsy_blocks_events.next().await {
-        eprintln!("Block {:?} finalized: {:?}", block.number, block.hash());
+        let cycle_guard = match matching_cycles.try_start(&trigger) {
+            Some(cycle_guard) => cycle_guard,
+            None => {
+                warn!(%trigger, reason = "the previous cycle is still running", "Skipping matching cycle");
+                continue;
+            }
+        };
+        let cycle_span = info_span!("matching_cycle", %trigger, algorithm = algorithm.name());
+        info!(parent: &cycle_span, "Starting matching cycle");
 
-        if (block.number as u64) % 4 == 0 {
-            eprintln!("{}", "Starting matching cycle".green());
+        let orderbook_url = orderbook_url.to_string();
+        let node_url = node_url.to_string();
+        let algorithm = Arc::clone(algorithm);
+        let signer = Arc::clone(signer);
+        let matching_cycles = matching_cycles.clone();
+        tokio::task::spawn(
+            async move {
+                run_matching_cycle(orderbook_url, node_url, algorithm.as_ref(), signer.as_ref(), &matching_cycles)
+                    .await;
+                matching_cycles.set_stage(None);
+                drop(cycle_guard);
+            }
+            .instrument(cycle_span),
+        );
+    }
+    warn!("Block subscription dropped");
+    HEALTH.set_node_subscribed(false);
+    Ok(())
+}
 
-            let orderbook_url_clone = Arc::clone(&orderbook_url);
-            let node_url_clone = Arc::clone(&node_url);
+async fn next_interval_tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => futures::future::pending().await,
+    }
+}
 
-            if let Err(error) = tokio::task::spawn(async move {
-                let orderbook_url_clone = orderbook_url_clone.lock().unwrap().to_string();
+async fn block_has_new_orders(
+    api: &gsy_node::RuntimeApi<DefaultConfig, SubstrateExtrinsicParams<DefaultConfig>>,
+    block_hash: H256,
+) -> Result<bool, MycoError> {
+    let events = api.events().at(block_hash).await?;
+    Ok(events
+        .find_first::<gsy_node::orderbook_worker::events::NewOrderInserted>()?
+        .is_some()
+        || events
+            .find_first::<gsy_node::orderbook_registry::events::NewOrderInserted>()?
+            .is_some()
+        || events
+            .find_first::<gsy_node::orderbook_registry::events::NewOrderInsertedByProxy>()?
+            .is_some())
+}
 
-                eprintln!(
-                    "{} {}",
-                    "Fetching orders from".green(),
-                    orderbook_url_clone.clone().green().bold()
-                );
+async fn run_matching_cycle(
+    orderbook_url: String,
+    node_url: String,
+    algorithm: &dyn MatchingAlgorithm,
+    signer: &MycoSigner,
+    matching_cycles: &MatchingCycles,
+) {
+    debug!(%orderbook_url, "Fetching open orders");
+    matching_cycles.set_stage(Some(String::from("fetching the open orders")));
 
-                let (open_bid, open_offer) =
-                    fetch_open_orders_from_orderbook_service(orderbook_url_clone)
-                        .await
-                        .unwrap_or_else(|e| panic!("Failed to fetch the open orders: {:?}", e));
+    let open_orders = fetch_open_orders_from_orderbook_service(orderbook_url).await;
+    HEALTH.set_orderbook_reachable(open_orders.is_ok());
+    let markets = match open_orders {
+        Ok(open_orders) => MatchingData::from_open_orders(open_orders),
+        Err(error) => {
+            error!(%error, "Error while fetching the orderbook");
+            return;
+        }
+    };
 
-                eprintln!("{} - {:?}", "Open Bid".blue(), open_bid);
-                eprintln!("{} - {:?}", "Open Offer".magenta(), open_offer);
-            })
-            .await
-            {
-                eprintln!(
-                    "{} - {:?}",
-                    "Error while fetching the orderbook".red(),
-                    error
-                );
-            }
-
-            // TODO: Modify Extrinsic to SettleTrade
-            tokio::task::spawn(async move {
-                let node_url_clone = node_url_clone.lock().unwrap().to_string();
-
-                let signer = PairSigner::new(AccountKeyring::Alice.pair());
-                eprintln!("Signer: {:?}", signer.account_id());
-                let dest = AccountKeyring::Bob.to_account_id().into();
-                let api =
-                    ClientBuilder::new()
-                        .set_url(node_url_clone)
-                        .build()
-                        .await
-                        .unwrap_or_else(|e| panic!("Failed to connect to node: {:?}", e))
-                        .to_runtime_api::<gsy_node::RuntimeApi<
-                            DefaultConfig,
-                            PolkadotExtrinsicParams<DefaultConfig>,
-                        >>();
-
-                let balance_transfer = api
-                    .tx()
-                    .balances()
-                    .transfer(dest, 10_000)
-                    .unwrap_or_else(|e| panic!("Failed to create the transfer extrinsic: {:?}", e))
-                    .sign_and_submit_then_watch_default(&signer)
-                    .await
-                    .unwrap_or_else(|e| panic!("Failed to submit the transfer extrinsic: {:?}", e))
-                    .wait_for_finalized_success()
-                    .await
-                    .unwrap_or_else(|e| {
-                        panic!(
-                        "Failed to fetch a successful response for the transfer extrinsic: {:?}",
-                        e
-                    )
-                    });
-
-                let transfer_event = balance_transfer
-                    .find_first::<gsy_node::balances::events::Transfer>()
-                    .unwrap_or_else(|e| panic!(
-                        "Failed to ensure that the transaction was successful by catching the associated events. {:?}",
-                        e
-                    ));
-
-                eprintln!("Balance transfer success: {:?}", transfer_event);
-            });
-        }
-    }
-    eprintln!("{}", "Subscription dropped.".bright_red().bold());
-    loop {
-        eprintln!("{}", "Trying to reconnect...".yellow());
-        let two_seconds = time::Duration::from_millis(2000);
-        thread::sleep(two_seconds);
-        let orderbook_url = orderbook_url.lock().unwrap().to_string();
-        let node_url = node_url.lock().unwrap().to_string();
-        if let Err(error) = substrate_subscribe(orderbook_url, node_url.clone()).await {
-            eprintln!("{} - {:?}", "Error".bright_red().bold(), error);
-        }
-    }
-}
-
-async fn fetch_open_orders_from_orderbook_service(
-    url: String,
-) -> Result<(Vec<Bid>, Vec<Offer>), Error> {
-    let res = reqwest::get(url).await?;
-    eprintln!("Response: {:?} {}", res.version(), res.status());
-    eprintln!("Headers: {:#?}\n", res.headers());
-
-    let body = res.json::<Vec<OrderSchema>>().await?;
-    let open_bid: Vec<Bid> = body
-        .clone()
-        .into_iter()
-        .filter(|order| {
-            order.status == OrderStatus::Open && matches!(order.order, Order::Bid { .. })
+    info!(
+        markets = markets.len(),
+        bids = markets.iter().map(|market| market.bids.len()).sum::<usize>(),
+        offers = markets.iter().map(|market| market.offers.len()).sum::<usize>(),
+        "Fetched open orders"
+    );
+    debug!(?markets, "Open orders");
+
+    let proposed_trades = match_open_orders(&markets, algorithm);
+    if proposed_trades.is_empty() {
+        info!("No matches found");
+        return;
+    }
+
+    matching_cycles.set_stage(Some(format!("settling {} trades", proposed_trades.len())));
+    if let Err(error) = settle_trades(node_url, proposed_trades, signer).await {
+        error!(%error, "Error while settling the trades");
+    }
+}
+
+async fn fetch_open_orders_from_orderbook_service(url: String) -> Result<Vec<OrderSchema>, MycoError> {
+    let orders = fetch_orders(&url).await?;
+    Ok(orders.into_iter().filter(|order| order.status == OrderStatus::Open).collect())
+}
+
+/// Fetches the orders of the orderbook service, whatever their status
+pub async fn fetch_orders(orderbook_url: &str) -> Result<Vec<OrderSchema>, MycoError> {
+    let _timer = metrics::ORDERBOOK_FETCH_DURATION.start_timer();
+    let res = reqwest::get(orderbook_url).await?;
+    debug!(version = ?res.version(), status = %res.status(), headers = ?res.headers(), "Orderbook response");
+
+    Ok(res.error_for_status()?.json::<Vec<OrderSchema>>().await?)
+}
+
+/// Inserts open orders into the orderbook service, for the nodes whose
+/// orderbook worker does not forward the orders it registers
+pub async fn insert_orders_into_orderbook(orderbook_url: &str, orders: &[Order]) -> Result<(), MycoError> {
+    let orders: Vec<OrderSchema> = orders.iter().cloned().map(OrderSchema::from).collect();
+    let res = reqwest::Client::new().post(orderbook_url).json(&orders).send().await?;
+    debug!(status = %res.status(), orders = orders.len(), "Orderbook insertion response");
+    res.error_for_status()?;
+    Ok(())
+}
+
+fn match_open_orders(markets: &[MatchingData], algorithm: &dyn MatchingAlgorithm) -> Vec<Trade> {
+    // The matching algorithms run on the web2 representation of the orders,
+    // the trades refer to the web3 orders by their hash in the orderbook
+    markets
+        .iter()
+        .flat_map(|market| {
+            let market_id = market.market_id();
+            let _market_span = info_span!("market", %market_id, time_slot = market.time_slot).entered();
+            metrics::record_orders(&market_id, market.bids.len(), market.offers.len());
+            let timer = metrics::MATCHING_DURATION.with_label_values(&[algorithm.name()]).start_timer();
+            let trades = algorithm.match_web3(market);
+            timer.observe_duration();
+            metrics::record_trades(algorithm.name(), &trades);
+            debug!(
+                bids = market.bids.len(),
+                offers = market.offers.len(),
+                trades = trades.len(),
+                "Market matched"
+            );
+            trades
         })
-        .map(|order| order.into())
-        .collect();
-    let open_offer: Vec<Offer> = body
-        .into_iter()
-        .filter(|order| {
-            order.status == OrderStatus::Open && matches!(order.order, Order::Offer { .. })
+        .collect()
+}
+
+fn to_chain_component(component: &OrderComponent) -> chain_orders::OrderComponent<AccountId32> {
+    chain_orders::OrderComponent {
+        energy: component.energy,
+        energy_rate: component.energy_rate,
+        pref_partners: component.pref_partners.clone(),
+        priority: component.priority,
+        energy_type: component.energy_type.clone(),
+    }
+}
+
+fn to_chain_bid(bid: &Bid) -> ChainBid {
+    ChainBid {
+        buyer: bid.buyer.clone(),
+        uuid: bid.uuid,
+        market_uuid: bid.market_uuid.clone(),
+        time_slot: bid.time_slot,
+        creation_time: bid.creation_time,
+        attributes: bid.attributes.clone(),
+        bid_component: to_chain_component(&bid.bid_component),
+    }
+}
+
+fn to_chain_offer(offer: &Offer) -> ChainOffer {
+    ChainOffer {
+        seller: offer.seller.clone(),
+        uuid: offer.uuid,
+        market_uuid: offer.market_uuid.clone(),
+        time_slot: offer.time_slot,
+        creation_time: offer.creation_time,
+        attributes: offer.attributes.clone(),
+        offer_component: to_chain_component(&offer.offer_component),
+    }
+}
+
+fn to_chain_order(order: &Order) -> ChainOrder {
+    match order {
+        Order::Bid(bid) => ChainOrder::Bid(to_chain_bid(bid)),
+        Order::Offer(offer) => ChainOrder::Offer(to_chain_offer(offer)),
+    }
+}
+
+fn to_chain_bid_offer_match(trade: &Trade) -> Result<ChainBidOfferMatch, MycoError> {
+    Ok(ChainBidOfferMatch {
+        market_id: settlement_market_id(&trade.bid.market_uuid)?,
+        time_slot: trade.bid.time_slot,
+        bid: to_chain_bid(&trade.bid),
+        offer: to_chain_offer(&trade.offer),
+        residual_offer: None,
+        residual_bid: None,
+        selected_energy: trade.selected_energy.to_chain_units()?,
+        energy_rate: trade.energy_rate.to_chain_units()?,
+    })
+}
+
+async fn settle_trades(
+    node_url: String,
+    proposed_trades: Vec<Trade>,
+    signer: &MycoSigner,
+) -> Result<(), MycoError> {
+    // Submits the proposed trades to the TradesSettlement pallet and reports
+    // which of them were executed by the chain. The trades the pallet cannot
+    // represent are left out, so that they do not hold back the others.
+    let (proposed_trades, proposed_matches): (Vec<&Trade>, Vec<ChainBidOfferMatch>) = proposed_trades
+        .iter()
+        .filter_map(|trade| match to_chain_bid_offer_match(trade) {
+            Ok(proposed_match) => Some((trade, proposed_match)),
+            Err(error) => {
+                warn!(bid_hash = ?trade.bid_hash, offer_hash = ?trade.offer_hash, %error, "Dropping trade");
+                None
+            }
         })
-        .map(|order| order.into())
+        .unzip();
+    if proposed_matches.is_empty() {
+        return Ok(());
+    }
+    let proposed_orders: Vec<(Vec<u8>, Vec<u8>)> = proposed_matches
+        .iter()
+        .map(|proposed_match| (proposed_match.bid.encode(), proposed_match.offer.encode()))
         .collect();
-    Ok((open_bid, open_offer))
-}
\ No newline at end of file
+
+    debug!(signer = %signer.account_id(), "Settling trades");
+    let api = ClientBuilder::new()
+        .set_url(node_url)
+        .build()
+        .await?
+        .to_runtime_api::<gsy_node::RuntimeApi<DefaultConfig, PolkadotExtrinsicParams<DefaultConfig>>>();
+
+    info!(trades = proposed_orders.len(), "Submitting trades");
+    let settlement: Result<_, subxt::BasicError> = async {
+        api.tx()
+            .trades_settlement()
+            .settle_trades(proposed_matches)?
+            .sign_and_submit_then_watch_default(signer)
+            .await?
+            .wait_for_finalized_success()
+            .await
+    }
+    .await;
+    metrics::record_extrinsic("settle_trades", settlement.is_ok());
+
+    let settlement = match settlement {
+        Ok(settlement) => settlement,
+        Err(error) => {
+            error!(trades = proposed_orders.len(), %error, "Rejected trades");
+            return Err(error.into());
+        }
+    };
+
+    for trade_cleared in settlement.find::<gsy_node::trades_settlement::events::TradeCleared>() {
+        info!(trade_cleared = ?trade_cleared?, "Trade cleared");
+    }
+
+    let executed_orders = settlement
+        .find::<gsy_node::orderbook_registry::events::OrderExecuted>()
+        .map(|order_executed| order_executed.map(|order_executed| order_executed.0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for ((bid, offer), proposed_trade) in proposed_orders.iter().zip(proposed_trades.iter()) {
+        let executed = executed_orders
+            .iter()
+            .any(|trade| trade.bid.encode() == *bid && trade.offer.encode() == *offer);
+        let bid_hash = proposed_trade.bid_hash;
+        let offer_hash = proposed_trade.offer_hash;
+        let energy = proposed_trade.selected_energy;
+        if executed {
+            info!(?bid_hash, ?offer_hash, %energy, "Accepted trade");
+        } else {
+            warn!(?bid_hash, ?offer_hash, %energy, "Rejected trade");
+        }
+    }
+    Ok(())
+}
+
+/// Order event emitted by the node for an extrinsic of the orderbook pallets
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderEvent {
+    /// Pallet and name of the event, e.g. `OrderbookWorker.NewOrderInserted`
+    pub name: &'static str,
+    /// Owner of the order
+    pub account: AccountId32,
+    /// Proxy account that submitted the extrinsic on behalf of the owner
+    pub proxy: Option<AccountId32>,
+    pub hash: H256,
+}
+
+impl fmt::Display for OrderEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?} account {}", self.name, self.hash, self.account)?;
+        if let Some(proxy) = &self.proxy {
+            write!(f, " proxy {}", proxy)?;
+        }
+        Ok(())
+    }
+}
+
+type NodeEvents = TransactionEvents<DefaultConfig, gsy_node::Event>;
+
+/// Registers the orders through the OrderbookWorker pallet, as the signer or
+/// as the proxy of the delegator, and returns the events of the insertion
+pub async fn submit_orders(
+    node_url: &str,
+    signer: &MycoSigner,
+    orders: &[Order],
+    delegator: Option<AccountId32>,
+) -> Result<Vec<OrderEvent>, MycoError> {
+    let chain_orders: Vec<ChainOrder> = orders.iter().map(to_chain_order).collect();
+    let api = connect_to_node(node_url).await?;
+
+    let extrinsic = if delegator.is_some() { "insert_orders_by_proxy" } else { "insert_orders" };
+    info!(signer = %signer.account_id(), ?delegator, orders = orders.len(), extrinsic, "Submitting orders");
+    let insertion: Result<NodeEvents, subxt::BasicError> = async {
+        let orderbook_worker = api.tx().orderbook_worker();
+        let progress = match delegator {
+            Some(delegator) => {
+                orderbook_worker
+                    .insert_orders_by_proxy(delegator, chain_orders)?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+            None => {
+                orderbook_worker
+                    .insert_orders(chain_orders)?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+        };
+        progress.wait_for_finalized_success().await
+    }
+    .await;
+    metrics::record_extrinsic(extrinsic, insertion.is_ok());
+    find_order_events(&insertion?)
+}
+
+/// Removes the orders of the signer with the given hashes through the
+/// OrderbookWorker pallet, one extrinsic per order, and returns the events
+/// of the removals
+pub async fn delete_orders(
+    node_url: &str,
+    signer: &MycoSigner,
+    hashes: &[H256],
+) -> Result<Vec<OrderEvent>, MycoError> {
+    let api = connect_to_node(node_url).await?;
+
+    let extrinsic = "remove_order_by_hash";
+    info!(signer = %signer.account_id(), orders = hashes.len(), extrinsic, "Removing orders");
+    let mut order_events = Vec::new();
+    for hash in hashes {
+        let removal: Result<NodeEvents, subxt::BasicError> = async {
+            api.tx()
+                .orderbook_worker()
+                .remove_order_by_hash(*hash)?
+                .sign_and_submit_then_watch_default(signer)
+                .await?
+                .wait_for_finalized_success()
+                .await
+        }
+        .await;
+        metrics::record_extrinsic(extrinsic, removal.is_ok());
+        order_events.extend(find_order_events(&removal?)?);
+    }
+    Ok(order_events)
+}
+
+/// Removes the orders of the delegator through the OrderbookWorker pallet,
+/// as its proxy, and returns the events of the removal. The pallet takes
+/// the orders in full since it has no removal by hash for proxies.
+pub async fn delete_orders_by_proxy(
+    node_url: &str,
+    signer: &MycoSigner,
+    orders: &[Order],
+    delegator: AccountId32,
+) -> Result<Vec<OrderEvent>, MycoError> {
+    let chain_orders: Vec<ChainOrder> = orders.iter().map(to_chain_order).collect();
+    let api = connect_to_node(node_url).await?;
+
+    let extrinsic = "remove_orders_by_proxy";
+    info!(signer = %signer.account_id(), %delegator, orders = orders.len(), extrinsic, "Removing orders");
+    let removal: Result<NodeEvents, subxt::BasicError> = async {
+        api.tx()
+            .orderbook_worker()
+            .remove_orders_by_proxy(delegator, chain_orders)?
+            .sign_and_submit_then_watch_default(signer)
+            .await?
+            .wait_for_finalized_success()
+            .await
+    }
+    .await;
+    metrics::record_extrinsic(extrinsic, removal.is_ok());
+    find_order_events(&removal?)
+}
+
+fn find_order_events(events: &NodeEvents) -> Result<Vec<OrderEvent>, MycoError> {
+    use gsy_node::{orderbook_registry::events as registry, orderbook_worker::events as worker};
+
+    let event = |name, account, proxy, hash| OrderEvent { name, account, proxy, hash };
+    let mut order_events = Vec::new();
+    for inserted in events.find::<worker::NewOrderInserted>() {
+        let inserted = inserted?;
+        order_events.push(event("OrderbookWorker.NewOrderInserted", inserted.0, None, inserted.1));
+    }
+    for removed in events.find::<worker::OrderRemoved>() {
+        let removed = removed?;
+        order_events.push(event("OrderbookWorker.OrderRemoved", removed.0, None, removed.1));
+    }
+    for inserted in events.find::<registry::NewOrderInserted>() {
+        let inserted = inserted?;
+        order_events.push(event("OrderbookRegistry.NewOrderInserted", inserted.0, None, inserted.1));
+    }
+    for inserted in events.find::<registry::NewOrderInsertedByProxy>() {
+        let inserted = inserted?;
+        order_events.push(event(
+            "OrderbookRegistry.NewOrderInsertedByProxy",
+            inserted.0,
+            Some(inserted.1),
+            inserted.2,
+        ));
+    }
+    for deleted in events.find::<registry::OrderDeleted>() {
+        let deleted = deleted?;
+        order_events.push(event("OrderbookRegistry.OrderDeleted", deleted.0, None, deleted.1));
+    }
+    for deleted in events.find::<registry::OrderDeletedByProxy>() {
+        let deleted = deleted?;
+        order_events.push(event(
+            "OrderbookRegistry.OrderDeletedByProxy",
+            deleted.0,
+            Some(deleted.1),
+            deleted.2,
+        ));
+    }
+    Ok(order_events)
+}
+
+/// Administration call of the GsyCollateral pallet
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminCall {
+    RegisterUser(AccountId32),
+    RegisterMycoOperator(AccountId32),
+    RegisterProxyAccount(AccountId32),
+    UnregisterProxyAccount(AccountId32),
+    DepositCollateral(u128),
+    WithdrawCollateral(u128),
+    ShutdownVault(AccountId32),
+    RestartVault(AccountId32),
+}
+
+impl AdminCall {
+    /// Name of the extrinsic of the GsyCollateral pallet
+    pub fn name(&self) -> &'static str {
+        match self {
+            AdminCall::RegisterUser(_) => "register_user",
+            AdminCall::RegisterMycoOperator(_) => "register_myco_operator",
+            AdminCall::RegisterProxyAccount(_) => "register_proxy_account",
+            AdminCall::UnregisterProxyAccount(_) => "unregister_proxy_account",
+            AdminCall::DepositCollateral(_) => "deposit_collateral",
+            AdminCall::WithdrawCollateral(_) => "withdraw_collateral",
+            AdminCall::ShutdownVault(_) => "shutdown_vault",
+            AdminCall::RestartVault(_) => "restart_vault",
+        }
+    }
+
+    /// The calls requiring the Root origin are dispatched through the Sudo pallet
+    pub fn requires_root(&self) -> bool {
+        matches!(
+            self,
+            AdminCall::RegisterUser(_)
+                | AdminCall::RegisterMycoOperator(_)
+                | AdminCall::ShutdownVault(_)
+                | AdminCall::RestartVault(_)
+        )
+    }
+
+    /// SCALE encoding of the runtime call submitted by the signer, wrapped in
+    /// a sudo call when it requires the Root origin
+    pub fn encoded_call(&self) -> Vec<u8> {
+        if self.requires_root() {
+            RuntimeCall::Sudo(SudoCall::sudo {
+                call: Box::new(self.to_runtime_call()),
+            })
+            .encode()
+        } else {
+            self.to_runtime_call().encode()
+        }
+    }
+
+    fn to_runtime_call(&self) -> RuntimeCall {
+        RuntimeCall::GsyCollateral(match self.clone() {
+            AdminCall::RegisterUser(user_account) => CollateralCall::register_user { user_account },
+            AdminCall::RegisterMycoOperator(myco_operator_account) => {
+                CollateralCall::register_myco_operator { myco_operator_account }
+            }
+            AdminCall::RegisterProxyAccount(proxy_account) => {
+                CollateralCall::register_proxy_account { proxy_account }
+            }
+            AdminCall::UnregisterProxyAccount(proxy_account) => {
+                CollateralCall::unregister_proxy_account { proxy_account }
+            }
+            AdminCall::DepositCollateral(amount) => CollateralCall::deposit_collateral { amount },
+            AdminCall::WithdrawCollateral(amount) => CollateralCall::withdraw_collateral { amount },
+            AdminCall::ShutdownVault(user_account) => CollateralCall::shutdown_vault { user_account },
+            AdminCall::RestartVault(user_account) => CollateralCall::restart_vault { user_account },
+        })
+    }
+}
+
+impl fmt::Display for AdminCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let argument = match self {
+            AdminCall::RegisterUser(account)
+            | AdminCall::RegisterMycoOperator(account)
+            | AdminCall::RegisterProxyAccount(account)
+            | AdminCall::UnregisterProxyAccount(account)
+            | AdminCall::ShutdownVault(account)
+            | AdminCall::RestartVault(account) => account.to_string(),
+            AdminCall::DepositCollateral(amount) | AdminCall::WithdrawCollateral(amount) => amount.to_string(),
+        };
+        if self.requires_root() {
+            write!(f, "sudo({}({}))", self.name(), argument)
+        } else {
+            write!(f, "{}({})", self.name(), argument)
+        }
+    }
+}
+
+/// Checks an administration call against the state of the node without
+/// submitting it, and returns the reasons why the node would reject it
+pub async fn check_admin_call(
+    node_url: &str,
+    signer: &MycoSigner,
+    call: &AdminCall,
+) -> Result<Vec<String>, MycoError> {
+    let api = connect_to_node(node_url).await?;
+    let signer_account = signer.account_id();
+
+    let mut problems = Vec::new();
+    if call.requires_root() {
+        let sudo_key = api.storage().sudo().key(None).await?;
+        if sudo_key != *signer_account {
+            problems.push(format!("the signer {} is not the sudo key {}", signer_account, sudo_key));
+        }
+    } else if !is_registered_user(&api, signer_account).await? {
+        problems.push(format!("the signer {} is not a registered user", signer_account));
+    }
+
+    match call {
+        AdminCall::RegisterUser(account) => {
+            if is_registered_user(&api, account).await? {
+                problems.push(format!("{} is already a registered user", account));
+            }
+        }
+        AdminCall::RegisterMycoOperator(account) => {
+            let registered_myco = api.storage().gsy_collateral().registered_myco(account, None).await?;
+            if registered_myco != H256::zero() {
+                problems.push(format!("{} is already a registered Myco operator", account));
+            }
+        }
+        AdminCall::RegisterProxyAccount(proxy_account) => {
+            if proxy_account == signer_account {
+                problems.push(String::from("a user cannot be its own proxy account"));
+            }
+        }
+        AdminCall::DepositCollateral(amount) => {
+            let free_balance = api.storage().system().account(signer_account, None).await?.data.free;
+            if free_balance < *amount {
+                problems.push(format!("the free balance of the signer is {}", free_balance));
+            }
+        }
+        AdminCall::ShutdownVault(account) | AdminCall::RestartVault(account) => {
+            if !is_registered_user(&api, account).await? {
+                problems.push(format!("{} is not a registered user", account));
+            }
+        }
+        AdminCall::UnregisterProxyAccount(_) | AdminCall::WithdrawCollateral(_) => {}
+    }
+    Ok(problems)
+}
+
+async fn is_registered_user(api: &NodeApi, account: &AccountId32) -> Result<bool, MycoError> {
+    // RegisteredUser is a value query, an unregistered account reads as the zero hash
+    let registered_user = api.storage().gsy_collateral().registered_user(account, None).await?;
+    Ok(registered_user != H256::zero())
+}
+
+/// Submits an administration call, through the Sudo pallet when it requires
+/// the Root origin, and returns the events it emitted
+pub async fn submit_admin_call(
+    node_url: &str,
+    signer: &MycoSigner,
+    call: &AdminCall,
+) -> Result<Vec<gsy_node::Event>, MycoError> {
+    let api = connect_to_node(node_url).await?;
+
+    info!(signer = %signer.account_id(), %call, "Submitting administration call");
+    let submission: Result<NodeEvents, subxt::BasicError> = async {
+        let gsy_collateral = api.tx().gsy_collateral();
+        let progress = match call {
+            AdminCall::RegisterProxyAccount(proxy_account) => {
+                gsy_collateral
+                    .register_proxy_account(proxy_account.clone())?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+            AdminCall::UnregisterProxyAccount(proxy_account) => {
+                gsy_collateral
+                    .unregister_proxy_account(proxy_account.clone())?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+            AdminCall::DepositCollateral(amount) => {
+                gsy_collateral
+                    .deposit_collateral(*amount)?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+            AdminCall::WithdrawCollateral(amount) => {
+                gsy_collateral
+                    .withdraw_collateral(*amount)?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+            AdminCall::RegisterUser(_)
+            | AdminCall::RegisterMycoOperator(_)
+            | AdminCall::ShutdownVault(_)
+            | AdminCall::RestartVault(_) => {
+                api.tx()
+                    .sudo()
+                    .sudo(call.to_runtime_call())?
+                    .sign_and_submit_then_watch_default(signer)
+                    .await?
+            }
+        };
+        progress.wait_for_finalized_success().await
+    }
+    .await;
+    metrics::record_extrinsic(call.name(), submission.is_ok());
+    let events = submission?;
+
+    // A sudo extrinsic succeeds even when the call it dispatches fails
+    if let Some(gsy_node::sudo::events::Sudid(Err(error))) =
+        events.find_first::<gsy_node::sudo::events::Sudid>()?
+    {
+        return Err(MycoError::Rejected {
+            call: call.to_string(),
+            reason: format!("{:?}", error),
+        });
+    }
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Ok(event) if matches!(event.event, gsy_node::Event::System(_)) => None,
+            event => Some(event.map(|event| event.event).map_err(MycoError::from)),
+        })
+        .collect()
+}
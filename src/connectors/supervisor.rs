@@ -0,0 +1,84 @@
+use crate::utils::{metrics, MycoError};
+use rand::Rng;
+use std::time::Duration;
+use tracing::{error, warn};
+
+/// How the connectors reconnect after losing their connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Consecutive failed attempts before giving up, 0 retries forever
+    pub max_attempts: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter: the delay doubles with each failed
+    /// attempt up to the max delay, and is randomised between half and all
+    /// of it so that several clients do not reconnect at the same time
+    pub fn delay(&self, failures: u32) -> Duration {
+        let backoff = self
+            .initial_delay
+            .checked_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let half = backoff / 2;
+        let jitter_millis = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_millis)
+    }
+}
+
+/// Keeps track of the reconnections of a connector. A session that ends
+/// with `Ok` was connected before it was lost, so it resets the failures.
+pub struct ReconnectSupervisor {
+    name: &'static str,
+    policy: RetryPolicy,
+    failures: u32,
+}
+
+impl ReconnectSupervisor {
+    pub fn new(name: &'static str, policy: RetryPolicy) -> Self {
+        Self {
+            name,
+            policy,
+            failures: 0,
+        }
+    }
+
+    /// Waits before the next connection attempt, or returns the error of
+    /// the last session once the attempts are exhausted
+    pub async fn reconnect(&mut self, session: Result<(), MycoError>) -> Result<(), MycoError> {
+        match session {
+            Ok(()) => {
+                warn!(connection = self.name, "Connection lost");
+                self.failures = 0;
+            }
+            Err(error) => {
+                error!(connection = self.name, %error, failures = self.failures + 1, "Connection failed");
+                self.failures += 1;
+                if self.policy.max_attempts > 0 && self.failures >= self.policy.max_attempts {
+                    return Err(MycoError::RetriesExhausted {
+                        name: self.name,
+                        attempts: self.failures,
+                        source: Box::new(error),
+                    });
+                }
+            }
+        }
+        metrics::RECONNECTS.with_label_values(&[self.name]).inc();
+        let delay = self.policy.delay(self.failures);
+        warn!(connection = self.name, ?delay, "Reconnecting");
+        tokio::time::sleep(delay).await;
+        Ok(())
+    }
+}
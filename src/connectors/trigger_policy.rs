@@ -0,0 +1,187 @@
+use crate::primitives::messages::SlotCompletion;
+use anyhow::{anyhow, Error, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// When the tick events of a market should trigger a request for its open orders
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TriggerPolicy {
+    /// Once per slot at each of the given completion percentages, e.g. "completion:33"
+    /// or "completion:25,50,75"
+    AtCompletion(Vec<u8>),
+    /// Every N ticks, e.g. "ticks:5"
+    EveryTicks(u32),
+}
+
+impl Default for TriggerPolicy {
+    fn default() -> Self {
+        TriggerPolicy::AtCompletion(vec![33])
+    }
+}
+
+impl FromStr for TriggerPolicy {
+    type Err = Error;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = policy
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid trigger policy {}, expected completion:<%,...> or ticks:<n>", policy))?;
+        match kind.trim() {
+            "completion" => {
+                let mut percentages = value
+                    .split(',')
+                    .map(|percentage| {
+                        percentage
+                            .trim()
+                            .trim_end_matches('%')
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|percentage| *percentage <= 100)
+                            .ok_or_else(|| anyhow!("Invalid slot completion {} in trigger policy", percentage))
+                    })
+                    .collect::<Result<Vec<u8>>>()?;
+                percentages.sort_unstable();
+                percentages.dedup();
+                Ok(TriggerPolicy::AtCompletion(percentages))
+            }
+            "ticks" => match value.trim().parse::<u32>() {
+                Ok(ticks) if ticks > 0 => Ok(TriggerPolicy::EveryTicks(ticks)),
+                _ => Err(anyhow!("Invalid number of ticks {} in trigger policy", value)),
+            },
+            _ => Err(anyhow!("Unknown trigger policy {}, expected completion or ticks", kind)),
+        }
+    }
+}
+
+impl fmt::Display for TriggerPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerPolicy::AtCompletion(percentages) => {
+                let percentages: Vec<String> = percentages.iter().map(u8::to_string).collect();
+                write!(f, "completion:{}", percentages.join(","))
+            }
+            TriggerPolicy::EveryTicks(ticks) => write!(f, "ticks:{}", ticks),
+        }
+    }
+}
+
+/// Applies a trigger policy to the tick events of one market.
+/// A new slot is detected when the slot completion goes backwards. At most
+/// one offers-bids request is outstanding per slot: while the response has
+/// not been received, the triggers are postponed to the following ticks.
+#[derive(Clone, Debug)]
+pub struct TickTrigger {
+    policy: TriggerPolicy,
+    last_completion: Option<SlotCompletion>,
+    next_completion_index: usize,
+    ticks_since_request: u32,
+    outstanding_request: bool,
+}
+
+impl TickTrigger {
+    pub fn new(policy: TriggerPolicy) -> Self {
+        Self {
+            policy,
+            last_completion: None,
+            next_completion_index: 0,
+            ticks_since_request: 0,
+            outstanding_request: false,
+        }
+    }
+
+    pub fn policy(&self) -> &TriggerPolicy {
+        &self.policy
+    }
+
+    pub fn has_outstanding_request(&self) -> bool {
+        self.outstanding_request
+    }
+
+    /// Records a tick and returns whether the open orders should be requested
+    pub fn on_tick(&mut self, completion: SlotCompletion) -> bool {
+        if matches!(self.last_completion, Some(last) if completion < last) {
+            // A request of the previous slot will not be answered for this slot
+            self.next_completion_index = 0;
+            self.outstanding_request = false;
+        }
+        self.last_completion = Some(completion);
+        self.ticks_since_request = self.ticks_since_request.saturating_add(1);
+
+        let triggered = match &self.policy {
+            TriggerPolicy::AtCompletion(percentages) => matches!(
+                percentages.get(self.next_completion_index),
+                Some(percentage) if completion.0 >= *percentage
+            ),
+            TriggerPolicy::EveryTicks(ticks) => self.ticks_since_request >= *ticks,
+        };
+        if !triggered || self.outstanding_request {
+            return false;
+        }
+
+        if let TriggerPolicy::AtCompletion(percentages) = &self.policy {
+            // Percentages already passed are covered by this request
+            self.next_completion_index = percentages
+                .iter()
+                .take_while(|percentage| **percentage <= completion.0)
+                .count();
+        }
+        self.ticks_since_request = 0;
+        self.outstanding_request = true;
+        true
+    }
+
+    /// Records that the requested open orders were received
+    pub fn on_response(&mut self) {
+        self.outstanding_request = false;
+    }
+}
+
+/// Tick triggers of one simulation, one per market and slot. Ticks that do not
+/// name their market or slot share the trigger of the unnamed market or slot.
+#[derive(Clone, Debug)]
+pub struct TickTriggers {
+    policy: TriggerPolicy,
+    triggers: HashMap<(Option<String>, Option<String>), TickTrigger>,
+}
+
+impl TickTriggers {
+    pub fn new(policy: TriggerPolicy) -> Self {
+        Self {
+            policy,
+            triggers: HashMap::new(),
+        }
+    }
+
+    /// Records a tick of a market slot and returns whether the open orders should be requested
+    pub fn on_tick(
+        &mut self,
+        market_id: Option<&str>,
+        market_slot: Option<&str>,
+        completion: SlotCompletion,
+    ) -> bool {
+        let key = (market_id.map(str::to_string), market_slot.map(str::to_string));
+        if market_slot.is_some() && !self.triggers.contains_key(&key) {
+            // A new slot of the market, its previous slots will not tick anymore
+            self.triggers
+                .retain(|(market, slot), _| slot.is_none() || market.as_deref() != market_id);
+        }
+        let policy = &self.policy;
+        self.triggers
+            .entry(key)
+            .or_insert_with(|| TickTrigger::new(policy.clone()))
+            .on_tick(completion)
+    }
+
+    /// Records that the requested open orders were received. A request covers all
+    /// the markets of the simulation, so it answers the requests of every slot.
+    pub fn on_response(&mut self) {
+        for trigger in self.triggers.values_mut() {
+            trigger.on_response();
+        }
+    }
+
+    pub fn has_outstanding_request(&self) -> bool {
+        self.triggers.values().any(TickTrigger::has_outstanding_request)
+    }
+}
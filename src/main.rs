@@ -1,61 +1,246 @@
 use clap::Parser;
-use myco_client_rust::connectors::{redis_subscribe, substrate_subscribe};
-use myco_client_rust::utils::{Cli, Commands};
-use std::{thread, time};
-use text_colorizer::*;
+use myco_client_rust::algorithms::{get_algorithm, MatchVerifier, MatchingAlgorithm};
+use myco_client_rust::connectors::{
+    check_admin_call, check_myco_operator, delete_orders, delete_orders_by_proxy, fetch_orders,
+    insert_orders_into_orderbook, load_signer, match_offers_bids, redis_subscribe,
+    submit_admin_call, submit_orders, substrate_subscribe, MycoSigner, OrderEvent, Shutdown,
+};
+use myco_client_rust::primitives::web3::{parse_account_id, Order, OrderStatus};
+use myco_client_rust::utils::{
+    format_matches, format_orders, init_logging, parse_order_hash, read_offers_bids, redact_url,
+    select_open_orders, serve_http, AdminCommands, Cli, Commands, Config, ConfigCommands,
+    OrderCommands, OutputFormat,
+};
+use std::fmt::Display;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use subxt::{sp_core::crypto::AccountId32, sp_core::hexdisplay::HexDisplay, sp_core::H256, Signer};
+use tracing::{error, info, warn};
+
+fn exit_with_error(context: &str, error: impl Display) -> ! {
+    error!(%error, "{}", context);
+    process::exit(1);
+}
+
+fn load_config(cli: &Cli) -> (Config, Arc<dyn MatchingAlgorithm>) {
+    let config = Config::load(cli.config.as_deref(), cli.config_layer())
+        .unwrap_or_else(|error| exit_with_error("Error", error));
+    let algorithm = get_algorithm(&config.algorithm)
+        .unwrap_or_else(|error| exit_with_error("Error", error));
+    (config, algorithm)
+}
+
+fn load_myco_signer(config: &Config) -> MycoSigner {
+    load_signer(config.signer.as_deref(), config.keystore.as_deref(), config.key_scheme)
+        .unwrap_or_else(|error| exit_with_error("Invalid signer", error))
+}
+
+fn listen_for_shutdown(config: &Config) -> Shutdown {
+    Shutdown::listen(config.shutdown_timeout).unwrap_or_else(|error| exit_with_error("Error", error))
+}
+
+fn exit_after_shutdown(shutdown: &Shutdown) -> ! {
+    info!(signal = ?shutdown.signal(), "Shut down");
+    process::exit(shutdown.exit_code());
+}
+
+fn start_http_server(config: &Config) {
+    let http_address = config.http_address;
+    let health_window = config.health_window;
+    tokio::spawn(async move {
+        if let Err(error) = serve_http(http_address, health_window).await {
+            exit_with_error("HTTP server error", error);
+        }
+    });
+}
+
+fn print_config(cli: &Cli) {
+    // The merged layers are validated before being printed, so that the
+    // printed configuration is the one the client would run with
+    let layers = Config::merge_layers(cli.config.as_deref(), cli.config_layer())
+        .and_then(|layers| Config::try_from(layers.clone()).map(|_| layers))
+        .unwrap_or_else(|error| exit_with_error("Error", error));
+    let config = toml::to_string(&layers.redacted())
+        .unwrap_or_else(|error| exit_with_error("Error", error));
+    print!("{}", config);
+}
+
+fn run_offline_match(cli: &Cli, input: Option<&Path>, format: OutputFormat) {
+    // The matches are verified against the orders of the payload,
+    // as the Redis connector does with the recommendations it receives
+    let (_, algorithm) = load_config(cli);
+    let response = read_offers_bids(input).unwrap_or_else(|error| exit_with_error("Error", error));
+    let mut verifier = MatchVerifier::new();
+    let matches = match_offers_bids(&response, algorithm.as_ref(), &mut verifier);
+    let output = format_matches(verifier.verify(&matches), format)
+        .unwrap_or_else(|error| exit_with_error("Error", error));
+    println!("{}", output);
+}
+
+fn parse_optional_account(address: Option<&str>) -> Option<AccountId32> {
+    address
+        .map(parse_account_id)
+        .transpose()
+        .unwrap_or_else(|error| exit_with_error("Error", error))
+}
+
+fn print_order_events(hashes: &[H256], events: &[OrderEvent]) {
+    // The hashes reported by the node are the ones of the orders it decoded,
+    // an order without event was not processed as the client encoded it
+    for &hash in hashes {
+        println!("{:?}", hash);
+        let order_events: Vec<_> = events.iter().filter(|event| event.hash == hash).collect();
+        if order_events.is_empty() {
+            warn!(?hash, "The node reported no event for the order");
+        }
+        for event in order_events {
+            println!("  {}", event);
+        }
+    }
+}
+
+async fn run_order_command(cli: &Cli, command: &OrderCommands) {
+    let (config, _) = load_config(cli);
+    match command {
+        OrderCommands::Submit { order, delegator, skip_orderbook, .. } => {
+            let signer = load_myco_signer(&config);
+            let delegator = parse_optional_account(delegator.as_deref());
+            // A proxy places the orders of its delegator
+            let account = delegator.clone().unwrap_or_else(|| signer.account_id().clone());
+            let creation_time = chrono::Utc::now().timestamp() as u64;
+            let orders = vec![order
+                .to_spec(account, creation_time)
+                .and_then(|spec| spec.to_order())
+                .unwrap_or_else(|error| exit_with_error("Error", error))];
+
+            let events = submit_orders(&config.node_url, &signer, &orders, delegator)
+                .await
+                .unwrap_or_else(|error| exit_with_error("Unable to register the order", error));
+            print_order_events(&orders.iter().map(Order::hash).collect::<Vec<_>>(), &events);
+            if !skip_orderbook {
+                if let Err(error) = insert_orders_into_orderbook(&config.orderbook_url, &orders).await {
+                    exit_with_error("Unable to insert the order into the orderbook service", error);
+                }
+            }
+        }
+        OrderCommands::Delete { hashes, delegator, .. } => {
+            let signer = load_myco_signer(&config);
+            let delegator = parse_optional_account(delegator.as_deref());
+            let hashes = hashes
+                .iter()
+                .map(|hash| parse_order_hash(hash))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|error| exit_with_error("Error", error));
+            let events = match delegator {
+                // The removal by proxy takes the orders in full, the orderbook service has them
+                Some(delegator) => {
+                    let orders = fetch_orders(&config.orderbook_url)
+                        .await
+                        .and_then(|orders| select_open_orders(orders, &hashes))
+                        .unwrap_or_else(|error| exit_with_error("Error", error));
+                    delete_orders_by_proxy(&config.node_url, &signer, &orders, delegator).await
+                }
+                None => delete_orders(&config.node_url, &signer, &hashes).await,
+            }
+            .unwrap_or_else(|error| exit_with_error("Unable to remove the orders", error));
+            print_order_events(&hashes, &events);
+        }
+        OrderCommands::List { account, all, format, .. } => {
+            let account = parse_optional_account(account.as_deref());
+            let mut orders = fetch_orders(&config.orderbook_url)
+                .await
+                .unwrap_or_else(|error| exit_with_error("Error", error));
+            orders.retain(|order| {
+                (*all || order.status == OrderStatus::Open)
+                    && account.as_ref().map_or(true, |account| order.order.account() == account)
+            });
+            let output = format_orders(&orders, *format).unwrap_or_else(|error| exit_with_error("Error", error));
+            println!("{}", output);
+        }
+    }
+}
+
+async fn run_admin_command(cli: &Cli, command: &AdminCommands) {
+    let (config, _) = load_config(cli);
+    let signer = load_myco_signer(&config);
+    let call = command.to_call().unwrap_or_else(|error| exit_with_error("Error", error));
+    println!("{} signed by {}", call, signer.account_id());
+
+    if !command.admin_args().execute {
+        let problems = check_admin_call(&config.node_url, &signer, &call)
+            .await
+            .unwrap_or_else(|error| exit_with_error("Error", error));
+        println!("call data 0x{}", HexDisplay::from(&call.encoded_call()));
+        if !problems.is_empty() {
+            for problem in problems {
+                println!("  would fail: {}", problem);
+            }
+            process::exit(1);
+        }
+        println!("Dry run, submit the call with --execute");
+        return;
+    }
+
+    let events = submit_admin_call(&config.node_url, &signer, &call)
+        .await
+        .unwrap_or_else(|error| exit_with_error("Call failed", error));
+    for event in events {
+        println!("  {:?}", event);
+    }
+}
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    init_logging(cli.verbose, cli.log_format);
 
     // You can check for the existence of subcommands, and if found use their
     // matches just as you would the top level cmd
     match &cli.command {
-        Commands::Web2 {
-            orderbook_host,
-            orderbook_port
-        } => async {
-            let orders_response_channel = String::from("external-myco/*/offers-bids/response/");
-            let recommendations_channel = String::from("external-myco/*/recommendations");
-            let tick_channel = String::from("external-myco/*/events/");
-
-            let channels = vec![
-                tick_channel.clone(),
-                orders_response_channel.clone(),
-                recommendations_channel.clone(),
-            ];
-
-            eprintln!("Connecting to: {}:{}", orderbook_host.green(), orderbook_port.green());
-
-            let url = format!("{}:{}", orderbook_host, orderbook_port);
-
-            if let Err(error) = redis_subscribe(channels.clone(), url).await {
-                eprintln!("{} - {:?}", "Error".red().bold(), error);
-                panic!("{:?}", error);
+        Commands::Web2 { .. } => async {
+            let (config, algorithm) = load_config(&cli);
+            let shutdown = listen_for_shutdown(&config);
+            start_http_server(&config);
+            info!(redis_url = %redact_url(&config.redis_url), "Connecting to Redis");
+
+            if let Err(error) = redis_subscribe(
+                config.channels.clone(),
+                config.redis_url.clone(),
+                algorithm,
+                config.trigger_policy.clone(),
+                config.retry_policy.clone(),
+                shutdown.clone(),
+            ).await {
+                exit_with_error("Error", error);
             }
+            exit_after_shutdown(&shutdown);
         }.await,
-        Commands::Web3 {
-            orderbook_host,
-            orderbook_port,
-            node_host,
-            node_port
-        } => async {
-            let orderbook_url = format!("{}:{}/{}", orderbook_host, orderbook_port, "orders");
-            let node_url = format!("{}:{}", node_host, node_port);
-            if let Err(error) = substrate_subscribe(orderbook_url.clone(), node_url.clone()).await {
-                eprintln!("{} - {:?}", "Error".bright_red().bold(), error);
-                let mut attempt: u8 = 1;
-                while attempt <= cli.max_attempts {
-                    eprintln!("{}\n{}: {}", "Retrying...".yellow(), "Attempt".yellow(), attempt.to_string().bright_white().bold());
-                    let two_seconds = time::Duration::from_millis(2000);
-                    thread::sleep(two_seconds);
-                    if let Err(error) = substrate_subscribe(orderbook_url.clone(), node_url.clone()).await {
-                        eprintln!("{} - {:?}", "Error".bright_red().bold(), error);
-                        attempt += 1;
-                    }
-                }
-                //panic!("{:?}", error);
+        Commands::Web3 { .. } => async {
+            let (config, algorithm) = load_config(&cli);
+            let shutdown = listen_for_shutdown(&config);
+            start_http_server(&config);
+            let signer = Arc::new(load_myco_signer(&config));
+            if let Err(error) = check_myco_operator(config.node_url.clone(), &signer).await {
+                exit_with_error("Refusing to start", error);
             }
-        }.await
+
+            if let Err(error) = substrate_subscribe(
+                config.orderbook_url.clone(),
+                config.node_url.clone(),
+                algorithm,
+                signer,
+                config.cadence.clone(),
+                config.retry_policy.clone(),
+                shutdown.clone(),
+            ).await {
+                exit_with_error("Error", error);
+            }
+            exit_after_shutdown(&shutdown);
+        }.await,
+        Commands::Match { input, format, .. } => run_offline_match(&cli, input.as_deref(), *format),
+        Commands::Order { command } => run_order_command(&cli, command).await,
+        Commands::Admin { command } => run_admin_command(&cli, command).await,
+        Commands::Config { command: ConfigCommands::Print } => print_config(&cli),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,501 @@
+//! Conversions between the web2 orders exchanged with gsy-e and the web3
+//! orders of the GSY node.
+//!
+//! - The quantities are fixed-point in both models: the web3 components store
+//!   the raw value of `Energy` and `EnergyRate`, i.e. the quantity multiplied
+//!   by 10^DECIMALS, in a `u32`.
+//! - The web3 time slots and creation times are Unix timestamps in seconds.
+//! - The web2 accounts are the SS58 addresses of the web3 accounts, written
+//!   with the default address format.
+//! - The market of the web3 orders is the `market_id` of the web2
+//!   `MatchingData` or `BidOfferMatch` they belong to, as UTF-8 bytes.
+//! - A web2 id that is a number below 256 is the web3 `uuid`. Any other id,
+//!   and the web2 fields that have no web3 counterpart, are kept as
+//!   `key=value` entries of the web3 attributes.
+//!
+//! A conversion fails rather than drop information, e.g. a quantity that
+//! does not fit in a `u32`, a time slot with sub-second precision or a web3
+//! attribute the web2 orders cannot represent.
+//!
+//! `web3::MatchingData::to_web2` builds on the same mapping, with the hash of
+//! the web3 orders as the web2 id. It drops the web3 fields the matching
+//! algorithms ignore (the priority and the attributes they do not know)
+//! instead of failing, and leaves out the orders that still cannot be
+//! represented, so that one order does not keep its market from being matched.
+
+use crate::primitives::quantities::{Energy, EnergyRate, Price};
+use crate::primitives::requirements::{BidRequirement, OfferRequirement, OrderAttributes};
+use crate::primitives::web3::parse_account_id;
+use crate::primitives::{web2, web3};
+use crate::utils::MycoError;
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use subxt::sp_core::crypto::AccountId32;
+use subxt::sp_core::H256;
+
+const ID: &str = "id";
+const ORIGINAL_PRICE: &str = "original_price";
+const ENERGY_TYPE: &str = "energy_type";
+const BID_ATTRIBUTES: &[&str] = &[ID, ORIGINAL_PRICE, ENERGY_TYPE, "buyer_origin", "buyer_origin_id", "buyer_id"];
+const OFFER_ATTRIBUTES: &[&str] = &[ID, ORIGINAL_PRICE, "seller_origin", "seller_origin_id", "seller_id"];
+
+/// How the web3 fields that the web2 orders cannot represent are handled
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// The conversion fails, so that no information is lost
+    Strict,
+    /// The fields are dropped, the matching algorithms do not use them
+    Matching,
+}
+
+/// Web2 representation of a web3 bid for the matching algorithms
+pub(crate) fn to_matching_bid(bid: web3::Bid) -> Result<web2::Bid, MycoError> {
+    to_web2_bid(bid, Mode::Matching)
+}
+
+/// Web2 representation of a web3 offer for the matching algorithms
+pub(crate) fn to_matching_offer(offer: web3::Offer) -> Result<web2::Offer, MycoError> {
+    to_web2_offer(offer, Mode::Matching)
+}
+
+impl TryFrom<web2::MatchingData> for web3::MatchingData {
+    type Error = MycoError;
+
+    /// The orders must share one time slot. The hashes are the ones of the
+    /// converted orders.
+    fn try_from(matching_data: web2::MatchingData) -> Result<Self, Self::Error> {
+        let market_uuid = to_market_uuid(&matching_data.market_id);
+        let mut time_slots = matching_data
+            .bids
+            .iter()
+            .map(|bid| bid.time_slot)
+            .chain(matching_data.offers.iter().map(|offer| offer.time_slot));
+        let time_slot = match time_slots.next() {
+            Some(time_slot) if time_slots.all(|other| other == time_slot) => time_slot,
+            Some(_) => return Err(lossy(format!("market {} has several time slots", matching_data.market_id))),
+            None => return Err(lossy(format!("market {} has no orders", matching_data.market_id))),
+        };
+        let time_slot = match time_slot {
+            Some(time_slot) => to_timestamp(&matching_data.market_id, "time slot", time_slot)?,
+            None => return Err(lossy(format!("market {} has no time slot", matching_data.market_id))),
+        };
+
+        let mut web3_matching_data = web3::MatchingData::new(market_uuid.clone(), time_slot);
+        for bid in matching_data.bids {
+            let bid = to_web3_bid(bid, market_uuid.clone())?;
+            web3_matching_data.bids.push((web3::Order::Bid(bid.clone()).hash(), bid));
+        }
+        for offer in matching_data.offers {
+            let offer = to_web3_offer(offer, market_uuid.clone())?;
+            web3_matching_data.offers.push((web3::Order::Offer(offer.clone()).hash(), offer));
+        }
+        Ok(web3_matching_data)
+    }
+}
+
+impl TryFrom<web3::MatchingData> for web2::MatchingData {
+    type Error = MycoError;
+
+    /// The web2 orders are identified by their web3 uuid, the hashes are not kept
+    fn try_from(matching_data: web3::MatchingData) -> Result<Self, Self::Error> {
+        let market_id = to_market_id(&matching_data.market_uuid)?;
+        let check_market = |market_uuid: &Option<Vec<u8>>, time_slot: u64, hash: &H256| {
+            if *market_uuid != matching_data.market_uuid || time_slot != matching_data.time_slot {
+                return Err(lossy(format!("order {:?} is not in market {}", hash, market_id)));
+            }
+            Ok(())
+        };
+        let mut bids = Vec::new();
+        for (hash, bid) in matching_data.bids.iter() {
+            check_market(&bid.market_uuid, bid.time_slot, hash)?;
+            bids.push(to_web2_bid(bid.clone(), Mode::Strict)?);
+        }
+        let mut offers = Vec::new();
+        for (hash, offer) in matching_data.offers.iter() {
+            check_market(&offer.market_uuid, offer.time_slot, hash)?;
+            offers.push(to_web2_offer(offer.clone(), Mode::Strict)?);
+        }
+        Ok(web2::MatchingData {
+            bids,
+            offers,
+            market_id,
+        })
+    }
+}
+
+impl TryFrom<web2::BidOfferMatch> for web3::Trade {
+    type Error = MycoError;
+
+    /// Converts a match recommended on Redis into a trade that can be settled on the node
+    fn try_from(bid_offer_match: web2::BidOfferMatch) -> Result<Self, Self::Error> {
+        let market_uuid = to_market_uuid(&bid_offer_match.market_id);
+        if bid_offer_match.time_slot.is_some()
+            && (bid_offer_match.time_slot != bid_offer_match.bid.time_slot
+                || bid_offer_match.time_slot != bid_offer_match.offer.time_slot)
+        {
+            return Err(lossy(format!(
+                "the orders of the match of bid {} and offer {} are not in its time slot",
+                bid_offer_match.bid.id, bid_offer_match.offer.id
+            )));
+        }
+        let bid = to_web3_bid(bid_offer_match.bid, market_uuid.clone())?;
+        let offer = to_web3_offer(bid_offer_match.offer, market_uuid)?;
+        Ok(web3::Trade {
+            bid_hash: web3::Order::Bid(bid.clone()).hash(),
+            offer_hash: web3::Order::Offer(offer.clone()).hash(),
+            bid,
+            offer,
+            selected_energy: bid_offer_match.selected_energy,
+            energy_rate: bid_offer_match.trade_rate,
+        })
+    }
+}
+
+impl TryFrom<web3::Trade> for web2::BidOfferMatch {
+    type Error = MycoError;
+
+    fn try_from(trade: web3::Trade) -> Result<Self, Self::Error> {
+        if trade.bid.market_uuid != trade.offer.market_uuid || trade.bid.time_slot != trade.offer.time_slot {
+            return Err(lossy(format!(
+                "the bid {:?} and the offer {:?} are not in the same market",
+                trade.bid_hash, trade.offer_hash
+            )));
+        }
+        Ok(web2::BidOfferMatch {
+            market_id: to_market_id(&trade.bid.market_uuid)?,
+            time_slot: Some(to_datetime(&format!("{:?}", trade.bid_hash), "time slot", trade.bid.time_slot)?),
+            bid: to_web2_bid(trade.bid, Mode::Strict)?,
+            selected_energy: trade.selected_energy,
+            offer: to_web2_offer(trade.offer, Mode::Strict)?,
+            trade_rate: trade.energy_rate,
+        })
+    }
+}
+
+fn to_web3_bid(bid: web2::Bid, market_uuid: Option<Vec<u8>>) -> Result<web3::Bid, MycoError> {
+    let mut attributes = Attributes::default();
+    let uuid = attributes.push_id(&bid.id);
+    attributes.push_price(bid.original_price);
+    attributes.push_origin("buyer_origin", &bid.buyer_origin, &bid.buyer);
+    attributes.push_origin("buyer_origin_id", &bid.buyer_origin_id, &bid.buyer);
+    attributes.push_origin("buyer_id", &bid.buyer_id, &bid.buyer);
+    if let Some(energy_type) = bid.attributes.and_then(|attributes| attributes.energy_type) {
+        attributes.push(ENERGY_TYPE, &energy_type);
+    }
+
+    let requirement = single_requirement(&bid.id, bid.requirements)?.unwrap_or_default();
+    if requirement.energy.is_some() || requirement.energy_rate.is_some() {
+        return Err(lossy(format!("bid {} has an energy or energy rate requirement", bid.id)));
+    }
+    let energy_type = match requirement.energy_type.as_deref() {
+        None => Vec::new(),
+        Some([energy_type]) => energy_type.as_bytes().to_vec(),
+        Some(_) => return Err(lossy(format!("bid {} accepts several energy types", bid.id))),
+    };
+
+    Ok(web3::Bid {
+        uuid,
+        market_uuid,
+        time_slot: required_timestamp(&bid.id, bid.time_slot)?,
+        creation_time: optional_timestamp(&bid.id, bid.creation_time)?,
+        attributes: attributes.into_bytes(),
+        bid_component: to_component(
+            &bid.id,
+            bid.energy,
+            bid.energy_rate,
+            requirement.trading_partners,
+            energy_type,
+        )?,
+        buyer: parse_account_id(&bid.buyer)?,
+    })
+}
+
+fn to_web3_offer(offer: web2::Offer, market_uuid: Option<Vec<u8>>) -> Result<web3::Offer, MycoError> {
+    let mut attributes = Attributes::default();
+    let uuid = attributes.push_id(&offer.id);
+    attributes.push_price(offer.original_price);
+    attributes.push_origin("seller_origin", &offer.seller_origin, &offer.seller);
+    attributes.push_origin("seller_origin_id", &offer.seller_origin_id, &offer.seller);
+    attributes.push_origin("seller_id", &offer.seller_id, &offer.seller);
+
+    let requirement = single_requirement(&offer.id, offer.requirements)?.unwrap_or_default();
+    let energy_type = offer
+        .attributes
+        .and_then(|attributes| attributes.energy_type)
+        .map(String::into_bytes)
+        .unwrap_or_default();
+
+    Ok(web3::Offer {
+        uuid,
+        market_uuid,
+        time_slot: required_timestamp(&offer.id, offer.time_slot)?,
+        creation_time: optional_timestamp(&offer.id, offer.creation_time)?,
+        attributes: attributes.into_bytes(),
+        offer_component: to_component(
+            &offer.id,
+            offer.energy,
+            offer.energy_rate,
+            requirement.trading_partners,
+            energy_type,
+        )?,
+        seller: parse_account_id(&offer.seller)?,
+    })
+}
+
+fn to_web2_bid(bid: web3::Bid, mode: Mode) -> Result<web2::Bid, MycoError> {
+    let mut attributes = Attributes::parse(&bid.attributes, BID_ATTRIBUTES, mode)?;
+    let id = attributes.take_id(bid.uuid)?;
+    let component = from_component(&id, bid.bid_component, mode)?;
+    let requirement = BidRequirement {
+        trading_partners: component.pref_partners,
+        energy_type: component.energy_type.map(|energy_type| vec![energy_type]),
+        ..Default::default()
+    };
+    Ok(web2::Bid {
+        r#type: String::from("Bid"),
+        energy: component.energy,
+        energy_rate: component.energy_rate,
+        original_price: attributes.take_price(&id)?,
+        attributes: attributes
+            .take(ENERGY_TYPE)
+            .map(|energy_type| OrderAttributes { energy_type: Some(energy_type) }),
+        requirements: if requirement == Default::default() {
+            None
+        } else {
+            Some(vec![requirement])
+        },
+        buyer_origin: attributes.take("buyer_origin").unwrap_or_else(|| bid.buyer.to_string()),
+        buyer_origin_id: attributes.take("buyer_origin_id").unwrap_or_else(|| bid.buyer.to_string()),
+        buyer_id: attributes.take("buyer_id").unwrap_or_else(|| bid.buyer.to_string()),
+        buyer: bid.buyer.to_string(),
+        time_slot: Some(to_datetime(&id, "time slot", bid.time_slot)?),
+        creation_time: bid
+            .creation_time
+            .map(|creation_time| to_datetime(&id, "creation time", creation_time))
+            .transpose()?,
+        id,
+    })
+}
+
+fn to_web2_offer(offer: web3::Offer, mode: Mode) -> Result<web2::Offer, MycoError> {
+    let mut attributes = Attributes::parse(&offer.attributes, OFFER_ATTRIBUTES, mode)?;
+    let id = attributes.take_id(offer.uuid)?;
+    let component = from_component(&id, offer.offer_component, mode)?;
+    let requirement = OfferRequirement {
+        trading_partners: component.pref_partners,
+    };
+    Ok(web2::Offer {
+        r#type: String::from("Offer"),
+        energy: component.energy,
+        energy_rate: component.energy_rate,
+        original_price: attributes.take_price(&id)?,
+        attributes: component
+            .energy_type
+            .map(|energy_type| OrderAttributes { energy_type: Some(energy_type) }),
+        requirements: if requirement == Default::default() {
+            None
+        } else {
+            Some(vec![requirement])
+        },
+        seller_origin: attributes.take("seller_origin").unwrap_or_else(|| offer.seller.to_string()),
+        seller_origin_id: attributes.take("seller_origin_id").unwrap_or_else(|| offer.seller.to_string()),
+        seller_id: attributes.take("seller_id").unwrap_or_else(|| offer.seller.to_string()),
+        seller: offer.seller.to_string(),
+        time_slot: Some(to_datetime(&id, "time slot", offer.time_slot)?),
+        creation_time: offer
+            .creation_time
+            .map(|creation_time| to_datetime(&id, "creation time", creation_time))
+            .transpose()?,
+        id,
+    })
+}
+
+fn to_component(
+    id: &str,
+    energy: Energy,
+    energy_rate: EnergyRate,
+    pref_partners: Option<Vec<String>>,
+    energy_type: Vec<u8>,
+) -> Result<web3::OrderComponent, MycoError> {
+    let to_chain_units = |quantity: Result<u32, _>, name: &str| {
+        quantity.map_err(|error| lossy(format!("{} of order {}: {}", name, id, error)))
+    };
+    let pref_partners = pref_partners
+        .map(|partners| partners.iter().map(|partner| parse_account_id(partner)).collect())
+        .transpose()?;
+    Ok(web3::OrderComponent {
+        energy: to_chain_units(energy.to_chain_units(), "energy")?,
+        energy_rate: to_chain_units(energy_rate.to_chain_units(), "energy rate")?,
+        pref_partners,
+        priority: 0,
+        energy_type,
+    })
+}
+
+/// Fields of a web3 order component, as represented in the web2 orders
+struct Component {
+    energy: Energy,
+    energy_rate: EnergyRate,
+    pref_partners: Option<Vec<String>>,
+    energy_type: Option<String>,
+}
+
+fn from_component(id: &str, component: web3::OrderComponent, mode: Mode) -> Result<Component, MycoError> {
+    if component.priority != 0 && mode == Mode::Strict {
+        return Err(lossy(format!("order {} has a priority, web2 orders have none", id)));
+    }
+    let energy_type = match component.energy_type.as_slice() {
+        [] => None,
+        energy_type => Some(
+            String::from_utf8(energy_type.to_vec())
+                .map_err(|_| lossy(format!("energy type of order {} is not UTF-8", id)))?,
+        ),
+    };
+    Ok(Component {
+        energy: Energy::from_chain_units(component.energy),
+        energy_rate: EnergyRate::from_chain_units(component.energy_rate),
+        pref_partners: component
+            .pref_partners
+            .map(|partners| partners.iter().map(AccountId32::to_string).collect()),
+        energy_type,
+    })
+}
+
+fn single_requirement<T>(id: &str, requirements: Option<Vec<T>>) -> Result<Option<T>, MycoError> {
+    let mut requirements = requirements.unwrap_or_default();
+    if requirements.len() > 1 {
+        return Err(lossy(format!("order {} has several requirements, web3 orders have one", id)));
+    }
+    Ok(requirements.pop())
+}
+
+/// Web2 fields kept as `key=value` entries of the web3 attributes
+#[derive(Default)]
+struct Attributes(BTreeMap<String, String>);
+
+impl Attributes {
+    fn parse(attributes: &[Vec<u8>], known_keys: &[&str], mode: Mode) -> Result<Self, MycoError> {
+        let mut entries = BTreeMap::new();
+        for attribute in attributes {
+            let attribute = String::from_utf8_lossy(attribute);
+            let (key, value) = match attribute
+                .split_once('=')
+                .filter(|(key, _)| known_keys.contains(key))
+            {
+                Some(entry) => entry,
+                None if mode == Mode::Matching => continue,
+                None => return Err(lossy(format!("web2 orders have no attribute {}", attribute))),
+            };
+            if entries.insert(key.to_string(), value.to_string()).is_some() {
+                return Err(lossy(format!("attribute {} is repeated", key)));
+            }
+        }
+        Ok(Attributes(entries))
+    }
+
+    fn push(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    /// Returns the web3 uuid of a web2 id, keeping the ids that do not fit in it
+    fn push_id(&mut self, id: &str) -> u8 {
+        match id.parse::<u8>() {
+            Ok(uuid) if uuid.to_string() == id => uuid,
+            _ => {
+                self.push(ID, id);
+                0
+            }
+        }
+    }
+
+    fn push_price(&mut self, original_price: Price) {
+        if !original_price.is_zero() {
+            self.push(ORIGINAL_PRICE, &original_price.to_string());
+        }
+    }
+
+    fn push_origin(&mut self, key: &str, origin: &str, owner: &str) {
+        if origin != owner {
+            self.push(key, origin);
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    fn take_id(&mut self, uuid: u8) -> Result<String, MycoError> {
+        match self.take(ID) {
+            Some(id) if uuid == 0 => Ok(id),
+            Some(id) => Err(lossy(format!("order {} also has the uuid {}", id, uuid))),
+            None => Ok(uuid.to_string()),
+        }
+    }
+
+    fn take_price(&mut self, id: &str) -> Result<Price, MycoError> {
+        match self.take(ORIGINAL_PRICE) {
+            Some(price) => price
+                .parse()
+                .map_err(|_| lossy(format!("invalid original price {} of order {}", price, id))),
+            None => Ok(Price::ZERO),
+        }
+    }
+
+    fn into_bytes(self) -> Vec<Vec<u8>> {
+        self.0
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value).into_bytes())
+            .collect()
+    }
+}
+
+fn to_market_uuid(market_id: &str) -> Option<Vec<u8>> {
+    if market_id.is_empty() {
+        None
+    } else {
+        Some(market_id.as_bytes().to_vec())
+    }
+}
+
+fn to_market_id(market_uuid: &Option<Vec<u8>>) -> Result<String, MycoError> {
+    match market_uuid {
+        Some(market_uuid) if market_uuid.is_empty() => {
+            Err(lossy(String::from("an empty market uuid cannot be told apart from no market uuid")))
+        }
+        Some(market_uuid) => String::from_utf8(market_uuid.clone())
+            .map_err(|_| lossy(format!("market uuid {:?} is not UTF-8", market_uuid))),
+        None => Ok(String::new()),
+    }
+}
+
+fn required_timestamp(id: &str, time_slot: Option<NaiveDateTime>) -> Result<u64, MycoError> {
+    match time_slot {
+        Some(time_slot) => to_timestamp(id, "time slot", time_slot),
+        None => Err(lossy(format!("order {} has no time slot", id))),
+    }
+}
+
+fn optional_timestamp(id: &str, creation_time: Option<NaiveDateTime>) -> Result<Option<u64>, MycoError> {
+    creation_time
+        .map(|creation_time| to_timestamp(id, "creation time", creation_time))
+        .transpose()
+}
+
+fn to_timestamp(id: &str, name: &str, datetime: NaiveDateTime) -> Result<u64, MycoError> {
+    if datetime.timestamp_subsec_nanos() != 0 {
+        return Err(lossy(format!("{} {} of {} is not a whole second", name, datetime, id)));
+    }
+    u64::try_from(datetime.timestamp())
+        .map_err(|_| lossy(format!("{} {} of {} is before 1970", name, datetime, id)))
+}
+
+fn to_datetime(id: &str, name: &str, timestamp: u64) -> Result<NaiveDateTime, MycoError> {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|timestamp| NaiveDateTime::from_timestamp_opt(timestamp, 0))
+        .ok_or_else(|| lossy(format!("{} {} of {} is out of range", name, timestamp, id)))
+}
+
+fn lossy(message: String) -> MycoError {
+    MycoError::LossyConversion(message)
+}
@@ -0,0 +1,126 @@
+use crate::algorithms::RejectionReason;
+use crate::primitives::web2::{Bid, BidOfferMatch, Offer};
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Open orders of a market for one time slot. Like the other messages, the
+/// fields added by newer gsy-e versions are ignored rather than rejected.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeSlotOrders {
+    #[serde(default)]
+    pub bids: Vec<Bid>,
+    #[serde(default)]
+    pub offers: Vec<Offer>,
+    #[serde(default, skip_serializing)]
+    pub market_id: Option<String>,
+}
+
+/// Message of the offers-bids response channel: the open orders
+/// grouped by market id and by time slot
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OffersBidsResponse {
+    pub bids_offers: BTreeMap<String, BTreeMap<String, TimeSlotOrders>>,
+}
+
+/// Completion of the current market slot, in percent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SlotCompletion(pub u8);
+
+impl<'de> Deserialize<'de> for SlotCompletion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // The exchange sends the completion as a string such as "45%"
+        let value = String::deserialize(deserializer)?;
+        value
+            .trim()
+            .trim_end_matches('%')
+            .parse::<u8>()
+            .ok()
+            .filter(|percent| *percent <= 100)
+            .map(SlotCompletion)
+            .ok_or_else(|| de::Error::custom(format!("invalid slot completion: {}", value)))
+    }
+}
+
+/// Message of the events channel
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickEvent {
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub slot_completion: Option<SlotCompletion>,
+    /// Market the tick belongs to, if the exchange names it
+    #[serde(default)]
+    pub market_id: Option<String>,
+    /// Slot of the market the tick belongs to, if the exchange names it
+    #[serde(default)]
+    pub market_slot: Option<String>,
+}
+
+/// Message of the recommendations channel
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecommendationsMessage {
+    pub recommended_matches: Vec<BidOfferMatch>,
+}
+
+/// Recommended match rejected by the verifier
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RejectedMatch {
+    #[serde(rename = "match")]
+    pub recommendation: BidOfferMatch,
+    pub reason: RejectionReason,
+    pub description: String,
+}
+
+/// Message published on the verification channel
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct VerificationResponse {
+    pub accepted_matches: Vec<BidOfferMatch>,
+    pub rejected_matches: Vec<RejectedMatch>,
+}
+
+/// Message published back when a received message cannot be processed
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ErrorResponse {
+    pub status: &'static str,
+    pub channel: String,
+    pub error: String,
+}
+
+/// Error raised when a message does not follow the schema of its channel
+#[derive(Debug)]
+pub struct MessageError {
+    pub channel: String,
+    pub source: serde_json::Error,
+}
+
+impl MessageError {
+    pub fn to_response(&self) -> ErrorResponse {
+        ErrorResponse {
+            status: "error",
+            channel: self.channel.clone(),
+            error: self.source.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed message on {}: {}", self.channel, self.source)
+    }
+}
+
+impl std::error::Error for MessageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Parses the payload of a message received on the given channel
+pub fn parse_message<T: DeserializeOwned>(channel: &str, payload: &str) -> Result<T, MessageError> {
+    serde_json::from_str(payload).map_err(|source| MessageError {
+        channel: channel.to_string(),
+        source,
+    })
+}
@@ -1,2 +1,6 @@
+mod conversions;
+pub mod messages;
+pub mod quantities;
+pub mod requirements;
 pub mod web2;
-pub mod web3;
+pub mod web3;
\ No newline at end of file
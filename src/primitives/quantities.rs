@@ -0,0 +1,210 @@
+use codec::{Decode, Encode};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of decimal places kept by the fixed-point quantities
+pub const DECIMALS: usize = 5;
+const SCALE: u64 = 10u64.pow(DECIMALS as u32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuantityError {
+    /// The value is negative, not a number or infinite
+    InvalidValue(String),
+    /// The decimal string has more decimal places than DECIMALS
+    TooPrecise(String),
+    /// The value does not fit in the fixed-point representation
+    Overflow(String),
+}
+
+impl fmt::Display for QuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuantityError::InvalidValue(value) => write!(f, "invalid quantity: {}", value),
+            QuantityError::TooPrecise(value) => {
+                write!(f, "quantity {} has more than {} decimal places", value, DECIMALS)
+            }
+            QuantityError::Overflow(value) => write!(f, "quantity {} is out of range", value),
+        }
+    }
+}
+
+impl std::error::Error for QuantityError {}
+
+macro_rules! fixed_point_quantity {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode,
+        )]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const ZERO: $name = $name(0);
+
+            /// Creates the quantity from its raw fixed-point value
+            pub const fn from_raw(raw: u64) -> Self {
+                $name(raw)
+            }
+
+            /// Raw fixed-point value, i.e. the quantity multiplied by 10^DECIMALS
+            pub const fn raw(&self) -> u64 {
+                self.0
+            }
+
+            /// Creates the quantity from its representation in the web3 primitives,
+            /// which store the raw fixed-point value
+            pub fn from_chain_units(value: u32) -> Self {
+                $name(value as u64)
+            }
+
+            /// Representation of the quantity in the web3 primitives, which store the raw
+            /// value in a u32, so quantities above 42949.67295 do not fit
+            pub fn to_chain_units(&self) -> Result<u32, QuantityError> {
+                u32::try_from(self.0).map_err(|_| QuantityError::Overflow(self.to_string()))
+            }
+
+            /// Creates the quantity from a float, rounded to DECIMALS decimal places
+            pub fn from_f64(value: f64) -> Result<Self, QuantityError> {
+                parse_f64(value).map($name)
+            }
+
+            pub fn to_f64(&self) -> f64 {
+                self.0 as f64 / SCALE as f64
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0 == 0
+            }
+
+            pub fn checked_add(&self, other: Self) -> Option<Self> {
+                self.0.checked_add(other.0).map($name)
+            }
+
+            pub fn checked_sub(&self, other: Self) -> Option<Self> {
+                self.0.checked_sub(other.0).map($name)
+            }
+
+            pub fn saturating_add(&self, other: Self) -> Self {
+                $name(self.0.saturating_add(other.0))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let fraction = format!("{:0width$}", self.0 % SCALE, width = DECIMALS);
+                let fraction = fraction.trim_end_matches('0');
+                if fraction.is_empty() {
+                    write!(f, "{}", self.0 / SCALE)
+                } else {
+                    write!(f, "{}.{}", self.0 / SCALE, fraction)
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = QuantityError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                parse_decimal(value).map($name)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_f64(self.to_f64())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(QuantityVisitor).map($name)
+            }
+        }
+    };
+}
+
+fixed_point_quantity!(
+    /// Energy in kWh, stored with DECIMALS decimal places. The chain only holds energies
+    /// up to about 42949.67 kWh, see `to_chain_units`
+    Energy
+);
+
+fixed_point_quantity!(
+    /// Energy rate in cents/kWh, stored with DECIMALS decimal places
+    EnergyRate
+);
+
+fixed_point_quantity!(
+    /// Price in cents, stored with DECIMALS decimal places
+    Price
+);
+
+fn parse_f64(value: f64) -> Result<u64, QuantityError> {
+    // Floats come from the wire, where 0.1 + 0.2 is sent as 0.30000000000000004, so
+    // they are rounded to DECIMALS decimal places. Only the decimal strings are strict.
+    parse_decimal(&format!("{:.*}", DECIMALS, value))
+}
+
+fn parse_decimal(value: &str) -> Result<u64, QuantityError> {
+    // Parses a decimal string such as "12.5" into its raw fixed-point value
+    let trimmed = value.trim();
+    let (integer, fraction) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(QuantityError::InvalidValue(value.to_string()));
+    }
+    if fraction.len() > DECIMALS {
+        return Err(QuantityError::TooPrecise(value.to_string()));
+    }
+
+    let integer: u64 = match integer {
+        "" => 0,
+        integer => integer
+            .parse()
+            .map_err(|_| QuantityError::Overflow(value.to_string()))?,
+    };
+    let fraction: u64 = format!("{:0<width$}", fraction, width = DECIMALS)
+        .parse()
+        .map_err(|_| QuantityError::InvalidValue(value.to_string()))?;
+
+    integer
+        .checked_mul(SCALE)
+        .and_then(|raw| raw.checked_add(fraction))
+        .ok_or_else(|| QuantityError::Overflow(value.to_string()))
+}
+
+/// Accepts both JSON numbers and decimal strings
+struct QuantityVisitor;
+
+impl<'de> Visitor<'de> for QuantityVisitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a non-negative number or decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+        value
+            .checked_mul(SCALE)
+            .ok_or_else(|| E::custom(QuantityError::Overflow(value.to_string())))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+        match u64::try_from(value) {
+            Ok(value) => self.visit_u64(value),
+            Err(_) => Err(E::custom(QuantityError::InvalidValue(value.to_string()))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<u64, E> {
+        parse_f64(value).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+        parse_decimal(value).map_err(E::custom)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::primitives::quantities::{Energy, EnergyRate};
+use serde::{Deserialize, Serialize};
+
+/// Requirement of a bid. A bid can list several requirements, and it can
+/// be matched with an offer that satisfies at least one of them.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct BidRequirement {
+    /// Sellers the buyer is willing to trade with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trading_partners: Option<Vec<String>>,
+    /// Energy types (e.g. "PV", "Wind") the buyer accepts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_type: Option<Vec<String>>,
+    /// Maximum energy the buyer wants to buy under this requirement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<Energy>,
+    /// Rate the buyer is willing to pay under this requirement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_rate: Option<EnergyRate>,
+}
+
+/// Requirement of an offer
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct OfferRequirement {
+    /// Buyers the seller is willing to trade with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trading_partners: Option<Vec<String>>,
+}
+
+/// Attributes describing the energy of an order
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct OrderAttributes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_type: Option<String>,
+}
+
+impl BidRequirement {
+    pub fn accepts_partner(&self, partners: &[&str]) -> bool {
+        match &self.trading_partners {
+            Some(trading_partners) => trading_partners
+                .iter()
+                .any(|partner| partners.contains(&partner.as_str())),
+            None => true,
+        }
+    }
+
+    pub fn accepts_energy_type(&self, attributes: Option<&OrderAttributes>) -> bool {
+        match &self.energy_type {
+            Some(energy_types) => match attributes.and_then(|a| a.energy_type.as_ref()) {
+                Some(energy_type) => energy_types.contains(energy_type),
+                None => false,
+            },
+            None => true,
+        }
+    }
+}
+
+impl OfferRequirement {
+    pub fn accepts_partner(&self, partners: &[&str]) -> bool {
+        match &self.trading_partners {
+            Some(trading_partners) => trading_partners
+                .iter()
+                .any(|partner| partners.contains(&partner.as_str())),
+            None => true,
+        }
+    }
+}
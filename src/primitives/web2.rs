@@ -1,4 +1,6 @@
-use serde::{Serialize, Deserialize, Serializer};
+use crate::primitives::quantities::{Energy, EnergyRate, Price};
+use crate::primitives::requirements::{BidRequirement, OfferRequirement, OrderAttributes};
+use serde::{de, Serialize, Deserialize, Deserializer, Serializer};
 use chrono::{NaiveDateTime};
 
 pub fn serialize_datetime<S>(
@@ -17,20 +19,41 @@ pub fn serialize_datetime<S>(
     }
 }
 
+pub fn deserialize_datetime<'de, D>(
+    deserializer: D
+) -> Result<Option<NaiveDateTime>, D::Error>
+    where
+        D: Deserializer<'de> {
+    // Accepts both the format written by serialize_datetime and the one sent by gsy-e
+    const FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"];
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(&s, format).ok())
+            .map(Some)
+            .ok_or_else(|| de::Error::custom(format!("invalid datetime: {}", s))),
+        None => Ok(None)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Bid {
     pub r#type: String,
     pub id: String,
-    pub energy: f32,
-    pub energy_rate: f32,
-    pub original_price: f32,
-    pub attributes: Option<String>,
-    pub requirements: Option<String>,
+    pub energy: Energy,
+    pub energy_rate: EnergyRate,
+    pub original_price: Price,
+    pub attributes: Option<OrderAttributes>,
+    pub requirements: Option<Vec<BidRequirement>>,
     pub buyer_origin: String,
     pub buyer_origin_id: String,
     pub buyer_id: String,
     pub buyer: String,
-    #[serde(serialize_with = "serialize_datetime")]
+    #[serde(
+        default,
+        serialize_with = "serialize_datetime",
+        deserialize_with = "deserialize_datetime"
+    )]
     pub time_slot: Option<NaiveDateTime>,
     pub creation_time: Option<NaiveDateTime>,
 }
@@ -39,16 +62,20 @@ pub struct Bid {
 pub struct Offer {
     pub r#type: String,
     pub id: String,
-    pub energy: f32,
-    pub energy_rate: f32,
-    pub original_price: f32,
-    pub attributes: Option<String>,
-    pub requirements: Option<String>,
+    pub energy: Energy,
+    pub energy_rate: EnergyRate,
+    pub original_price: Price,
+    pub attributes: Option<OrderAttributes>,
+    pub requirements: Option<Vec<OfferRequirement>>,
     pub seller_origin: String,
     pub seller_origin_id: String,
     pub seller_id: String,
     pub seller: String,
-    #[serde(serialize_with = "serialize_datetime")]
+    #[serde(
+        default,
+        serialize_with = "serialize_datetime",
+        deserialize_with = "deserialize_datetime"
+    )]
     pub time_slot: Option<NaiveDateTime>,
     pub creation_time: Option<NaiveDateTime>,
 }
@@ -56,12 +83,16 @@ pub struct Offer {
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct BidOfferMatch {
     pub market_id: String,
-    #[serde(serialize_with = "serialize_datetime")]
+    #[serde(
+        default,
+        serialize_with = "serialize_datetime",
+        deserialize_with = "deserialize_datetime"
+    )]
     pub time_slot: Option<NaiveDateTime>,
     pub bid: Bid,
-    pub selected_energy: f32,
+    pub selected_energy: Energy,
     pub offer: Offer,
-    pub trade_rate: f32,
+    pub trade_rate: EnergyRate,
 }
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
@@ -1,7 +1,16 @@
+use crate::primitives::conversions::{to_matching_bid, to_matching_offer};
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::requirements::{BidRequirement, OfferRequirement, OrderAttributes};
+use crate::primitives::web2;
+use crate::utils::MycoError;
 use codec::Encode;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use subxt::sp_core::crypto::AccountId32;
 use subxt::sp_core::H256;
 use subxt::sp_runtime::traits::{BlakeTwo256, Hash};
+use tracing::warn;
 
 #[derive(Serialize, Deserialize, Debug, Encode, Clone, PartialEq)]
 #[serde(tag = "type", content = "data")]
@@ -11,20 +20,73 @@ pub enum Order {
 }
 
 impl Order {
+    /// Identifier of the order: the hash of its SCALE encoding, which is the
+    /// hash the node reports in its NewOrderInserted events
     pub fn hash(&self) -> H256 {
         BlakeTwo256::hash_of(self)
     }
+
+    /// Buyer of a bid or seller of an offer
+    pub fn account(&self) -> &AccountId32 {
+        match self {
+            Order::Bid(bid) => &bid.buyer,
+            Order::Offer(offer) => &offer.seller,
+        }
+    }
+}
+
+/// Parses an SS58 account address
+pub fn parse_account_id(address: &str) -> Result<AccountId32, MycoError> {
+    AccountId32::from_str(address).map_err(|error| {
+        MycoError::InvalidOrder(format!("invalid account address {}: {}", address, error))
+    })
+}
+
+/// Market uuid of the orders of a market of the TradesSettlement pallet,
+/// which identifies the markets with a single byte
+pub fn market_uuid(market_id: u8) -> Vec<u8> {
+    vec![market_id]
 }
-/// Order component struct
+
+/// Market id of the TradesSettlement pallet for the market uuid of an order,
+/// the inverse of `market_uuid` with no market uuid as market 0
+pub fn settlement_market_id(market_uuid: &Option<Vec<u8>>) -> Result<u8, MycoError> {
+    match market_uuid.as_deref() {
+        None | Some([]) => Ok(0),
+        Some([market_id]) => Ok(*market_id),
+        Some(market_uuid) => Err(MycoError::InvalidOrder(format!(
+            "market uuid {:?} does not fit in the market id of the TradesSettlement pallet",
+            market_uuid
+        ))),
+    }
+}
+
+fn to_addresses(accounts: &Option<Vec<AccountId32>>) -> Option<Vec<String>> {
+    accounts
+        .as_ref()
+        .map(|accounts| accounts.iter().map(AccountId32::to_string).collect())
+}
+
+/// Order component struct. The accounts are SS58 addresses in JSON.
 #[derive(Serialize, Deserialize, Debug, Encode, Clone, PartialEq)]
 pub struct OrderComponent {
     pub energy: u32,
     pub energy_rate: u32,
-    pub pref_partners: Option<Vec<String>>,
+    pub pref_partners: Option<Vec<AccountId32>>,
     pub priority: u32,
     pub energy_type: Vec<u8>,
 }
 
+impl OrderComponent {
+    /// Energy type of the order, decoded from its UTF-8 representation
+    pub fn energy_type(&self) -> Option<String> {
+        if self.energy_type.is_empty() {
+            return None;
+        }
+        String::from_utf8(self.energy_type.clone()).ok()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Encode, Clone, PartialEq)]
 pub struct OrderSchema {
     pub _id: H256,
@@ -62,10 +124,13 @@ impl Default for OrderStatus {
     }
 }
 
-/// Bid order struct
+/// Bid order struct. The fields and their types are the ones of the node's
+/// pallets, so that the order hashes match the ones of the chain.
 #[derive(Serialize, Deserialize, Debug, Encode, Clone, PartialEq)]
 pub struct Bid {
-    pub buyer: String,
+    pub buyer: AccountId32,
+    /// Discriminator of the orders of a buyer that are otherwise identical.
+    /// It is a `u8` in the pallets, the orders are identified by their hash.
     pub uuid: u8,
     pub market_uuid: Option<Vec<u8>>,
     pub time_slot: u64,
@@ -74,28 +139,44 @@ pub struct Bid {
     pub bid_component: OrderComponent,
 }
 
-impl From<Order> for Bid {
-    fn from(order: Order) -> Self {
-        match order {
-            Order::Bid(bid) => bid,
-            _ => panic!("Expected Order::Bid"),
+impl Bid {
+    /// Requirement of the bid on the preferred partners and on the energy type
+    pub fn requirement(&self) -> BidRequirement {
+        BidRequirement {
+            trading_partners: to_addresses(&self.bid_component.pref_partners),
+            energy_type: self.bid_component.energy_type().map(|energy_type| vec![energy_type]),
+            ..Default::default()
         }
     }
 }
 
-impl From<OrderSchema> for Bid {
-    fn from(order: OrderSchema) -> Self {
-        match order.order {
-            Order::Bid(bid) => bid,
-            _ => panic!("Expected Order::Bid"),
+impl TryFrom<Order> for Bid {
+    type Error = MycoError;
+
+    fn try_from(order: Order) -> Result<Self, Self::Error> {
+        match order {
+            Order::Bid(bid) => Ok(bid),
+            Order::Offer(offer) => Err(MycoError::InvalidOrder(format!(
+                "expected a bid, got the offer {:?}",
+                Order::Offer(offer).hash()
+            ))),
         }
     }
 }
 
-/// Offer (Ask) order struct
+impl TryFrom<OrderSchema> for Bid {
+    type Error = MycoError;
+
+    fn try_from(order: OrderSchema) -> Result<Self, Self::Error> {
+        Bid::try_from(order.order)
+    }
+}
+
+/// Offer (Ask) order struct, see `Bid`
 #[derive(Serialize, Deserialize, Debug, Encode, Clone, PartialEq)]
 pub struct Offer {
-    pub seller: String,
+    pub seller: AccountId32,
+    /// Discriminator of the orders of a seller that are otherwise identical
     pub uuid: u8,
     pub market_uuid: Option<Vec<u8>>,
     pub time_slot: u64,
@@ -104,20 +185,161 @@ pub struct Offer {
     pub offer_component: OrderComponent,
 }
 
-impl From<Order> for Offer {
-    fn from(order: Order) -> Self {
+impl Offer {
+    /// Requirement of the offer on the preferred partners
+    pub fn requirement(&self) -> OfferRequirement {
+        OfferRequirement {
+            trading_partners: to_addresses(&self.offer_component.pref_partners),
+        }
+    }
+
+    /// Attributes of the energy sold by the offer
+    pub fn order_attributes(&self) -> OrderAttributes {
+        OrderAttributes {
+            energy_type: self.offer_component.energy_type(),
+        }
+    }
+}
+
+impl TryFrom<Order> for Offer {
+    type Error = MycoError;
+
+    fn try_from(order: Order) -> Result<Self, Self::Error> {
         match order {
-            Order::Offer(offer) => offer,
-            _ => panic!("Expected Order::Offer"),
+            Order::Offer(offer) => Ok(offer),
+            Order::Bid(bid) => Err(MycoError::InvalidOrder(format!(
+                "expected an offer, got the bid {:?}",
+                Order::Bid(bid).hash()
+            ))),
         }
     }
 }
 
-impl From<OrderSchema> for Offer {
-    fn from(order: OrderSchema) -> Self {
-        match order.order {
-            Order::Offer(offer) => offer,
-            _ => panic!("Expected Order::Offer"),
+impl TryFrom<OrderSchema> for Offer {
+    type Error = MycoError;
+
+    fn try_from(order: OrderSchema) -> Result<Self, Self::Error> {
+        Offer::try_from(order.order)
+    }
+}
+
+/// Open orders of a market for one time slot. The orders are kept with
+/// their hash in the orderbook, so that the trades can refer to them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MatchingData {
+    pub market_uuid: Option<Vec<u8>>,
+    pub time_slot: u64,
+    pub bids: Vec<(H256, Bid)>,
+    pub offers: Vec<(H256, Offer)>,
+}
+
+impl MatchingData {
+    pub fn new(market_uuid: Option<Vec<u8>>, time_slot: u64) -> Self {
+        MatchingData {
+            market_uuid,
+            time_slot,
+            bids: Vec::new(),
+            offers: Vec::new(),
         }
     }
-}
\ No newline at end of file
+
+    /// Groups the open orders of the orderbook by market and time slot
+    pub fn from_open_orders(orders: Vec<OrderSchema>) -> Vec<MatchingData> {
+        let mut markets: BTreeMap<(Option<Vec<u8>>, u64), MatchingData> = BTreeMap::new();
+        for order in orders.into_iter().filter(|order| order.status == OrderStatus::Open) {
+            let (market_uuid, time_slot) = match &order.order {
+                Order::Bid(bid) => (bid.market_uuid.clone(), bid.time_slot),
+                Order::Offer(offer) => (offer.market_uuid.clone(), offer.time_slot),
+            };
+            let matching_data = markets
+                .entry((market_uuid.clone(), time_slot))
+                .or_insert_with(|| MatchingData::new(market_uuid, time_slot));
+            match order.order {
+                Order::Bid(bid) => matching_data.bids.push((order._id, bid)),
+                Order::Offer(offer) => matching_data.offers.push((order._id, offer)),
+            }
+        }
+        markets.into_values().collect()
+    }
+
+    /// Market id of the web2 orders, the market uuid decoded as UTF-8
+    pub fn market_id(&self) -> String {
+        self.market_uuid
+            .as_ref()
+            .map(|market_uuid| String::from_utf8_lossy(market_uuid).to_string())
+            .unwrap_or_default()
+    }
+
+    /// Orders the matching algorithms run on: the web2 representation of the
+    /// orders (see `primitives::conversions`), identified by the hash of the
+    /// web3 orders since their uuids are not unique. The orders that cannot be
+    /// represented are left out, the other orders of the market are still matched.
+    pub fn to_web2(&self) -> web2::MatchingData {
+        let bids = self
+            .bids
+            .iter()
+            .filter_map(|(hash, bid)| match to_matching_bid(bid.clone()) {
+                Ok(bid) => Some(web2::Bid { id: web2_order_id(hash), ..bid }),
+                Err(error) => {
+                    warn!(?hash, %error, "Leaving the bid out of the matching");
+                    None
+                }
+            })
+            .collect();
+        let offers = self
+            .offers
+            .iter()
+            .filter_map(|(hash, offer)| match to_matching_offer(offer.clone()) {
+                Ok(offer) => Some(web2::Offer { id: web2_order_id(hash), ..offer }),
+                Err(error) => {
+                    warn!(?hash, %error, "Leaving the offer out of the matching");
+                    None
+                }
+            })
+            .collect();
+        web2::MatchingData {
+            bids,
+            offers,
+            market_id: self.market_id(),
+        }
+    }
+
+    /// Maps the matches of the web2 orders back to trades between the web3
+    /// orders. Matches of unknown orders are left out.
+    pub fn to_trades(&self, matches: &[web2::BidOfferMatch]) -> Vec<Trade> {
+        let bids: HashMap<String, &(H256, Bid)> =
+            self.bids.iter().map(|bid| (web2_order_id(&bid.0), bid)).collect();
+        let offers: HashMap<String, &(H256, Offer)> =
+            self.offers.iter().map(|offer| (web2_order_id(&offer.0), offer)).collect();
+        matches
+            .iter()
+            .filter_map(|bid_offer_match| {
+                let (bid_hash, bid) = bids.get(&bid_offer_match.bid.id)?;
+                let (offer_hash, offer) = offers.get(&bid_offer_match.offer.id)?;
+                Some(Trade {
+                    bid_hash: *bid_hash,
+                    offer_hash: *offer_hash,
+                    bid: bid.clone(),
+                    offer: offer.clone(),
+                    selected_energy: bid_offer_match.selected_energy,
+                    energy_rate: bid_offer_match.trade_rate,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Trade proposed between two open orders, identified by their hash in the orderbook
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub bid_hash: H256,
+    pub offer_hash: H256,
+    pub bid: Bid,
+    pub offer: Offer,
+    pub selected_energy: Energy,
+    pub energy_rate: EnergyRate,
+}
+
+fn web2_order_id(hash: &H256) -> String {
+    format!("{:?}", hash)
+}
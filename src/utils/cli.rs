@@ -1,4 +1,12 @@
-use clap::{Parser, Subcommand};
+use crate::algorithms::ALGORITHMS;
+use crate::connectors::{AdminCall, KeyScheme, MatchingCadence, TriggerPolicy};
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::web3::parse_account_id;
+use crate::utils::{redact_url, ConfigLayer, LogFormat, MycoError, OrderSide, OrderSpec, OutputFormat};
+use clap::{Args, Parser, Subcommand};
+use subxt::sp_core::crypto::AccountId32;
+use std::path::PathBuf;
+use tracing::warn;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -6,31 +14,459 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[clap(subcommand)]
     pub command: Commands,
-    #[clap(short, long)]
+    /// Logs the debug events of the client, unless RUST_LOG is set
+    #[clap(short, long, global = true)]
     pub verbose: bool,
-    #[clap(short, long, default_value_t = 3)]
-    pub max_attempts: u8,
+    /// Output format of the logs
+    #[clap(long, global = true, arg_enum, default_value = "text")]
+    pub log_format: LogFormat,
+    /// Configuration file (TOML or YAML), overridden by the MYCO_* variables and the flags
+    #[clap(short, long, global = true, parse(from_os_str))]
+    pub config: Option<PathBuf>,
+    /// Consecutive failed connection attempts before giving up, 0 retries forever [default: 3]
+    #[clap(short, long, global = true)]
+    pub max_attempts: Option<u32>,
+    /// Maximum delay between two connection attempts, in seconds [default: 30]
+    #[clap(long, global = true)]
+    pub max_delay: Option<u64>,
+    /// Address of the HTTP server exposing /metrics [default: 0.0.0.0:9464]
+    #[clap(long, global = true)]
+    pub http_address: Option<String>,
+    /// Seconds without tick or block after which /healthz fails [default: 60]
+    #[clap(long, global = true)]
+    pub health_window: Option<u64>,
+    /// Seconds given to the running matching cycle to complete on SIGINT/SIGTERM [default: 8]
+    #[clap(long, global = true)]
+    pub shutdown_timeout: Option<u64>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Web2 version
     Web2{
-        #[clap(default_value_t = String::from("redis://127.0.0.1"))]
-        orderbook_host: String,
-        #[clap(default_value_t = String::from("6379"))]
-        orderbook_port: String,
+        /// Redis URL [default: redis://127.0.0.1:6379]
+        #[clap(long)]
+        redis_url: Option<String>,
+        /// Deprecated, use --redis-url
+        #[clap(hide = true)]
+        orderbook_host: Option<String>,
+        /// Deprecated, use --redis-url
+        #[clap(hide = true)]
+        orderbook_port: Option<String>,
+        /// Matching algorithm used to produce the recommendations [default: pay_as_bid]
+        #[clap(long, possible_values = ALGORITHMS)]
+        algorithm: Option<String>,
+        /// When the ticks trigger the matching: completion:<%,...> or ticks:<n> [default: completion:33]
+        #[clap(long)]
+        trigger_policy: Option<TriggerPolicy>,
+        /// Channel pattern to subscribe to, can be repeated
+        #[clap(long = "channel", multiple_occurrences = true)]
+        channels: Vec<String>,
     },
 
     /// Web3 version
     Web3{
-        #[clap(default_value_t = String::from("http://127.0.0.1"))]
-        orderbook_host: String,
-        #[clap(default_value_t = String::from("8080"))]
-        orderbook_port: String,
-        #[clap(default_value_t = String::from("ws://127.0.0.1"))]
-        node_host: String,
-        #[clap(default_value_t = String::from("9944"))]
-        node_port: String,
+        /// Orderbook service URL [default: http://127.0.0.1:8080/orders]
+        #[clap(long)]
+        orderbook_url: Option<String>,
+        /// GSY node URL [default: ws://127.0.0.1:9944]
+        #[clap(long)]
+        node_url: Option<String>,
+        /// Matching algorithm used to produce the trades [default: pay_as_bid]
+        #[clap(long, possible_values = ALGORITHMS)]
+        algorithm: Option<String>,
+        /// Secret URI, mnemonic or hex seed of the Myco operator key
+        #[clap(long)]
+        signer: Option<String>,
+        /// File holding the secret phrase of the Myco operator key, as a JSON string like the node
+        /// keystore or as plain text (encrypted polkadot-js exports are not supported)
+        #[clap(long, parse(from_os_str))]
+        keystore: Option<PathBuf>,
+        /// Signature scheme of the Myco operator key [default: sr25519]
+        #[clap(long, possible_values = ["sr25519", "ed25519"])]
+        key_scheme: Option<KeyScheme>,
+        /// When the matching cycles start: blocks:<n>, interval:<duration> or new-order [default: blocks:4]
+        #[clap(long)]
+        cadence: Option<MatchingCadence>,
+        /// Deprecated, use --orderbook-url
+        #[clap(hide = true)]
+        orderbook_host: Option<String>,
+        /// Deprecated, use --orderbook-url
+        #[clap(hide = true)]
+        orderbook_port: Option<String>,
+        /// Deprecated, use --node-url
+        #[clap(hide = true)]
+        node_host: Option<String>,
+        /// Deprecated, use --node-url
+        #[clap(hide = true)]
+        node_port: Option<String>,
+    },
+
+    /// Runs a matching algorithm over an offers-bids response payload, without Redis
+    Match{
+        /// JSON file with the `bids_offers` payload, read from stdin when omitted or -
+        #[clap(parse(from_os_str))]
+        input: Option<PathBuf>,
+        /// Matching algorithm used to produce the recommendations [default: pay_as_bid]
+        #[clap(long, possible_values = ALGORITHMS)]
+        algorithm: Option<String>,
+        /// Prints the recommended_matches JSON or a table of the verified matches
+        #[clap(long, arg_enum, default_value = "json")]
+        format: OutputFormat,
+    },
+
+    /// Places, deletes and lists the orders of the GSY node
+    Order{
+        #[clap(subcommand)]
+        command: OrderCommands,
+    },
+
+    /// Registers users, proxies and Myco operators and manages the collateral vaults
+    Admin{
+        #[clap(subcommand)]
+        command: AdminCommands,
+    },
+
+    /// Configuration of the client
+    Config{
+        #[clap(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Prints the effective configuration, with the secrets redacted
+    Print,
+}
+
+#[derive(Subcommand)]
+pub enum OrderCommands {
+    /// Registers an order on the node and inserts it into the orderbook service
+    Submit{
+        #[clap(flatten)]
+        order: OrderArgs,
+        /// Places the order as the proxy of this account (SS58 address), which owns the order
+        #[clap(long)]
+        delegator: Option<String>,
+        /// Only registers the order on the node, when its orderbook worker forwards the orders
+        #[clap(long)]
+        skip_orderbook: bool,
+        /// Orderbook service URL [default: http://127.0.0.1:8080/orders]
+        #[clap(long)]
+        orderbook_url: Option<String>,
+        #[clap(flatten)]
+        node: NodeArgs,
+    },
+    /// Removes open orders from the node
+    Delete{
+        /// Hashes of the orders to remove
+        #[clap(required = true)]
+        hashes: Vec<String>,
+        /// Removes the orders as the proxy of this account (SS58 address), which owns the orders.
+        /// The orders are then looked up in the orderbook service.
+        #[clap(long)]
+        delegator: Option<String>,
+        /// Orderbook service URL [default: http://127.0.0.1:8080/orders]
+        #[clap(long)]
+        orderbook_url: Option<String>,
+        #[clap(flatten)]
+        node: NodeArgs,
+    },
+    /// Lists the orders of the orderbook service
+    List{
+        /// Only lists the orders of this account (SS58 address)
+        #[clap(long)]
+        account: Option<String>,
+        /// Also lists the executed and deleted orders
+        #[clap(long)]
+        all: bool,
+        /// Prints the orders as JSON or as a table
+        #[clap(long, arg_enum, default_value = "table")]
+        format: OutputFormat,
+        /// Orderbook service URL [default: http://127.0.0.1:8080/orders]
+        #[clap(long)]
+        orderbook_url: Option<String>,
+    },
+}
+
+/// Extrinsics of the GsyCollateral pallet. The ones marked (sudo) are
+/// dispatched through the Sudo pallet and must be signed by the sudo key.
+#[derive(Subcommand)]
+pub enum AdminCommands {
+    /// Registers a user account (sudo)
+    RegisterUser{
+        /// SS58 address of the user
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Registers a Myco operator account (sudo)
+    RegisterMycoOperator{
+        /// SS58 address of the Myco operator
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Registers a proxy account of the signing user
+    RegisterProxy{
+        /// SS58 address of the proxy account
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Unregisters a proxy account of the signing user
+    UnregisterProxy{
+        /// SS58 address of the proxy account
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Deposits collateral into the vault of the signing user
+    Deposit{
+        /// Amount, in the smallest unit of the balance
+        amount: u128,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Withdraws collateral from the vault of the signing user
+    Withdraw{
+        /// Amount, in the smallest unit of the balance
+        amount: u128,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Shuts the vault of a user down, in case of emergency (sudo)
+    ShutdownVault{
+        /// SS58 address of the owner of the vault
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+    /// Restarts the vault of a user after a shutdown (sudo)
+    RestartVault{
+        /// SS58 address of the owner of the vault
+        account: String,
+        #[clap(flatten)]
+        admin: AdminArgs,
+    },
+}
+
+impl AdminCommands {
+    /// Call of the GsyCollateral pallet submitted by the command
+    pub fn to_call(&self) -> Result<AdminCall, MycoError> {
+        Ok(match self {
+            AdminCommands::RegisterUser { account, .. } => AdminCall::RegisterUser(parse_account_id(account)?),
+            AdminCommands::RegisterMycoOperator { account, .. } => {
+                AdminCall::RegisterMycoOperator(parse_account_id(account)?)
+            }
+            AdminCommands::RegisterProxy { account, .. } => {
+                AdminCall::RegisterProxyAccount(parse_account_id(account)?)
+            }
+            AdminCommands::UnregisterProxy { account, .. } => {
+                AdminCall::UnregisterProxyAccount(parse_account_id(account)?)
+            }
+            AdminCommands::Deposit { amount, .. } => AdminCall::DepositCollateral(*amount),
+            AdminCommands::Withdraw { amount, .. } => AdminCall::WithdrawCollateral(*amount),
+            AdminCommands::ShutdownVault { account, .. } => AdminCall::ShutdownVault(parse_account_id(account)?),
+            AdminCommands::RestartVault { account, .. } => AdminCall::RestartVault(parse_account_id(account)?),
+        })
+    }
+
+    pub fn admin_args(&self) -> &AdminArgs {
+        match self {
+            AdminCommands::RegisterUser { admin, .. }
+            | AdminCommands::RegisterMycoOperator { admin, .. }
+            | AdminCommands::RegisterProxy { admin, .. }
+            | AdminCommands::UnregisterProxy { admin, .. }
+            | AdminCommands::Deposit { admin, .. }
+            | AdminCommands::Withdraw { admin, .. }
+            | AdminCommands::ShutdownVault { admin, .. }
+            | AdminCommands::RestartVault { admin, .. } => admin,
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Options shared by the admin commands
+#[derive(Args)]
+pub struct AdminArgs {
+    /// Submits the extrinsic, the command only checks it against the state of the node otherwise
+    #[clap(long)]
+    pub execute: bool,
+    #[clap(flatten)]
+    pub node: NodeArgs,
+}
+
+/// Connection to the GSY node and key of the account signing the extrinsics
+#[derive(Args)]
+pub struct NodeArgs {
+    /// GSY node URL [default: ws://127.0.0.1:9944]
+    #[clap(long)]
+    pub node_url: Option<String>,
+    /// Secret URI, mnemonic or hex seed of the signing key
+    #[clap(long)]
+    pub signer: Option<String>,
+    /// File holding the secret phrase of the signing key, as a JSON string like the node keystore
+    /// or as plain text (encrypted polkadot-js exports are not supported)
+    #[clap(long, parse(from_os_str))]
+    pub keystore: Option<PathBuf>,
+    /// Signature scheme of the signing key [default: sr25519]
+    #[clap(long, possible_values = ["sr25519", "ed25519"])]
+    pub key_scheme: Option<KeyScheme>,
+}
+
+impl NodeArgs {
+    fn apply(&self, layer: &mut ConfigLayer) {
+        layer.node_url = self.node_url.clone();
+        layer.signer = self.signer.clone();
+        layer.keystore = self.keystore.clone();
+        layer.key_scheme = self.key_scheme.as_ref().map(KeyScheme::to_string);
+    }
+}
+
+/// Order placed by the `order submit` command
+#[derive(Args)]
+pub struct OrderArgs {
+    /// Places a bid or an offer
+    #[clap(arg_enum)]
+    pub side: OrderSide,
+    /// Energy of the order, in kWh
+    #[clap(long)]
+    pub energy: Energy,
+    /// Energy rate of the order, in cents/kWh
+    #[clap(long)]
+    pub energy_rate: EnergyRate,
+    /// Time slot of the order, as a Unix timestamp in seconds
+    #[clap(long)]
+    pub time_slot: u64,
+    /// Market of the order, the single byte market id of the TradesSettlement pallet
+    #[clap(long)]
+    pub market: Option<u8>,
+    /// Tells apart the otherwise identical orders of an account
+    #[clap(long, default_value = "0")]
+    pub uuid: u8,
+    /// Energy type of the order, e.g. PV
+    #[clap(long)]
+    pub energy_type: Option<String>,
+    /// Preferred trading partner (SS58 address), can be repeated
+    #[clap(long = "partner", multiple_occurrences = true)]
+    pub partners: Vec<String>,
+    /// Priority of the order
+    #[clap(long, default_value = "0")]
+    pub priority: u32,
+}
+
+impl OrderArgs {
+    /// Order of the account, created at the given Unix timestamp
+    pub fn to_spec(&self, account: AccountId32, creation_time: u64) -> Result<OrderSpec, MycoError> {
+        Ok(OrderSpec {
+            side: self.side,
+            account,
+            uuid: self.uuid,
+            market: self.market,
+            time_slot: self.time_slot,
+            creation_time: Some(creation_time),
+            energy: self.energy,
+            energy_rate: self.energy_rate,
+            energy_type: self.energy_type.clone(),
+            partners: self
+                .partners
+                .iter()
+                .map(|partner| parse_account_id(partner))
+                .collect::<Result<_, _>>()?,
+            priority: self.priority,
+        })
+    }
+}
+
+/// URL given with the deprecated positional host and port arguments, which
+/// the URL flags replace
+fn deprecated_url(flag: &str, host: Option<&str>, port: Option<&str>, default_port: &str, path: &str) -> Option<String> {
+    let url = format!("{}:{}{}", host?, port.unwrap_or(default_port), path);
+    warn!("The positional host and port arguments are deprecated, use --{} {}", flag, redact_url(&url));
+    Some(url)
+}
+
+impl Cli {
+    /// Settings given on the command line, the top layer of the configuration
+    pub fn config_layer(&self) -> ConfigLayer {
+        let mut layer = ConfigLayer {
+            max_attempts: self.max_attempts,
+            max_delay: self.max_delay,
+            http_address: self.http_address.clone(),
+            health_window: self.health_window,
+            shutdown_timeout: self.shutdown_timeout,
+            ..Default::default()
+        };
+        match &self.command {
+            Commands::Web2 {
+                redis_url,
+                orderbook_host,
+                orderbook_port,
+                algorithm,
+                trigger_policy,
+                channels,
+            } => {
+                let deprecated_redis_url = deprecated_url(
+                    "redis-url",
+                    orderbook_host.as_deref(),
+                    orderbook_port.as_deref(),
+                    "6379",
+                    "",
+                );
+                layer.redis_url = redis_url.clone().or(deprecated_redis_url);
+                layer.algorithm = algorithm.clone();
+                layer.trigger_policy = trigger_policy.as_ref().map(TriggerPolicy::to_string);
+                if !channels.is_empty() {
+                    layer.channels = Some(channels.clone());
+                }
+            }
+            Commands::Web3 {
+                orderbook_url,
+                node_url,
+                algorithm,
+                signer,
+                keystore,
+                key_scheme,
+                cadence,
+                orderbook_host,
+                orderbook_port,
+                node_host,
+                node_port,
+            } => {
+                let deprecated_orderbook_url = deprecated_url(
+                    "orderbook-url",
+                    orderbook_host.as_deref(),
+                    orderbook_port.as_deref(),
+                    "8080",
+                    "/orders",
+                );
+                let deprecated_node_url =
+                    deprecated_url("node-url", node_host.as_deref(), node_port.as_deref(), "9944", "");
+                layer.orderbook_url = orderbook_url.clone().or(deprecated_orderbook_url);
+                layer.node_url = node_url.clone().or(deprecated_node_url);
+                layer.algorithm = algorithm.clone();
+                layer.signer = signer.clone();
+                layer.keystore = keystore.clone();
+                layer.key_scheme = key_scheme.as_ref().map(KeyScheme::to_string);
+                layer.cadence = cadence.as_ref().map(MatchingCadence::to_string);
+            }
+            Commands::Match { algorithm, .. } => {
+                layer.algorithm = algorithm.clone();
+            }
+            Commands::Order { command } => match command {
+                OrderCommands::Submit { orderbook_url, node, .. }
+                | OrderCommands::Delete { orderbook_url, node, .. } => {
+                    layer.orderbook_url = orderbook_url.clone();
+                    node.apply(&mut layer);
+                }
+                OrderCommands::List { orderbook_url, .. } => {
+                    layer.orderbook_url = orderbook_url.clone();
+                }
+            },
+            Commands::Admin { command } => command.admin_args().node.apply(&mut layer),
+            Commands::Config { .. } => {}
+        }
+        layer
+    }
+}
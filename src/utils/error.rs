@@ -0,0 +1,65 @@
+use crate::primitives::messages::MessageError;
+use crate::primitives::quantities::QuantityError;
+use thiserror::Error;
+
+/// Errors of the Myco client. A failure is reported for the message, block
+/// or matching cycle being processed, it does not stop the client.
+#[derive(Debug, Error)]
+pub enum MycoError {
+    // Parse errors
+    #[error(transparent)]
+    Message(#[from] MessageError),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid quantity: {0}")]
+    Quantity(#[from] QuantityError),
+    #[error("invalid order: {0}")]
+    InvalidOrder(String),
+    #[error("conversion would lose information: {0}")]
+    LossyConversion(String),
+    #[error("unable to read {path}: {source}")]
+    Input {
+        path: String,
+        source: std::io::Error,
+    },
+
+    // Transport errors
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+    #[error("cannot publish Redis message to {channel}: {source}")]
+    Publish {
+        channel: String,
+        source: redis::RedisError,
+    },
+    #[error("giving up on {name} after {attempts} failed attempts: {source}")]
+    RetriesExhausted {
+        name: &'static str,
+        attempts: u32,
+        source: Box<MycoError>,
+    },
+
+    #[error("HTTP server error: {0}")]
+    Http(#[from] hyper::Error),
+    #[error("unable to listen for signals: {0}")]
+    Signal(std::io::Error),
+
+    // Chain errors
+    #[error("node error: {0}")]
+    Chain(#[from] subxt::BasicError),
+    #[error("account {0} is not a registered Myco operator")]
+    NotMycoOperator(String),
+    #[error("the node rejected {call}: {reason}")]
+    Rejected { call: String, reason: String },
+
+    // Orderbook errors
+    #[error("orderbook service error: {0}")]
+    Orderbook(#[from] reqwest::Error),
+
+    // Configuration errors
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    // Algorithm errors
+    #[error("unknown matching algorithm {0}")]
+    UnknownAlgorithm(String),
+}
@@ -0,0 +1,69 @@
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Liveness and readiness of the connectors, reported by /healthz and /readyz
+pub struct Health {
+    redis_subscribed: AtomicBool,
+    node_subscribed: AtomicBool,
+    orderbook_reachable: AtomicBool,
+    last_activity: Mutex<Instant>,
+}
+
+pub static HEALTH: Lazy<Health> = Lazy::new(|| Health {
+    redis_subscribed: AtomicBool::new(false),
+    node_subscribed: AtomicBool::new(false),
+    orderbook_reachable: AtomicBool::new(false),
+    // The client is given one window after the start to see its first tick or block
+    last_activity: Mutex::new(Instant::now()),
+});
+
+impl Health {
+    pub fn set_redis_subscribed(&self, subscribed: bool) {
+        self.redis_subscribed.store(subscribed, Ordering::Relaxed);
+    }
+
+    pub fn set_node_subscribed(&self, subscribed: bool) {
+        self.node_subscribed.store(subscribed, Ordering::Relaxed);
+    }
+
+    pub fn set_orderbook_reachable(&self, reachable: bool) {
+        self.orderbook_reachable.store(reachable, Ordering::Relaxed);
+    }
+
+    /// Records that a tick or a block was seen
+    pub fn record_activity(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+    }
+
+    /// Ready when Redis is subscribed, or when the node subscription is live
+    /// and the orderbook service is reachable
+    pub fn readiness(&self) -> Result<(), String> {
+        if self.redis_subscribed.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        match (
+            self.node_subscribed.load(Ordering::Relaxed),
+            self.orderbook_reachable.load(Ordering::Relaxed),
+        ) {
+            (true, true) => Ok(()),
+            (true, false) => Err(String::from("the orderbook service is not reachable")),
+            (false, _) => Err(String::from("not subscribed to Redis or to the node")),
+        }
+    }
+
+    /// Healthy when a tick or a block was seen within the window
+    pub fn liveness(&self, window: Duration) -> Result<(), String> {
+        let elapsed = match self.last_activity.lock() {
+            Ok(last_activity) => last_activity.elapsed(),
+            Err(_) => return Err(String::from("the health state is poisoned")),
+        };
+        if elapsed > window {
+            return Err(format!("no tick or block seen for {}s", elapsed.as_secs()));
+        }
+        Ok(())
+    }
+}
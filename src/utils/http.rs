@@ -0,0 +1,69 @@
+use crate::utils::health::HEALTH;
+use crate::utils::MycoError;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use prometheus::{Encoder, TextEncoder};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Serves the monitoring endpoints of the client. /healthz fails when no
+/// tick or block was seen within the health window.
+pub async fn serve_http(address: SocketAddr, health_window: Duration) -> Result<(), MycoError> {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |request| handle_request(request, health_window)))
+    });
+    let server = Server::try_bind(&address)?.serve(make_service);
+    info!(%address, "Serving /metrics, /healthz and /readyz");
+    server.await?;
+    Ok(())
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    health_window: Duration,
+) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => metrics(),
+        (&Method::GET, "/healthz") => probe(HEALTH.liveness(health_window)),
+        (&Method::GET, "/readyz") => probe(HEALTH.readiness()),
+        _ => with_status(StatusCode::NOT_FOUND, Body::empty()),
+    };
+    Ok(response)
+}
+
+fn probe(result: Result<(), String>) -> Response<Body> {
+    let (status, body) = match result {
+        Ok(()) => (StatusCode::OK, serde_json::json!({ "status": "ok" })),
+        Err(reason) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            serde_json::json!({ "status": "unavailable", "reason": reason }),
+        ),
+    };
+    let mut response = with_status(status, Body::from(body.to_string()));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn with_status(status: StatusCode, body: Body) -> Response<Body> {
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response
+}
+
+fn metrics() -> Response<Body> {
+    let mut buffer = Vec::new();
+    if let Err(error) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        error!(%error, "Unable to encode the metrics");
+        return with_status(StatusCode::INTERNAL_SERVER_ERROR, Body::empty());
+    }
+    let mut response = Response::new(Body::from(buffer));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(prometheus::TEXT_FORMAT));
+    response
+}
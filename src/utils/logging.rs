@@ -0,0 +1,24 @@
+use clap::ArgEnum;
+use tracing_subscriber::EnvFilter;
+
+/// Output format of the logs
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Installs the global subscriber. RUST_LOG takes precedence over `--verbose`,
+/// which logs the debug events of the client.
+pub fn init_logging(verbose: bool, format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::new(if verbose { "info,myco_client_rust=debug" } else { "info" })
+    });
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().with_current_span(true).with_span_list(true).init(),
+    }
+}
@@ -0,0 +1,123 @@
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::web2::BidOfferMatch;
+use crate::primitives::web3::Trade;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_counter_vec, register_gauge_vec, register_histogram, register_histogram_vec,
+    register_int_counter_vec, CounterVec, GaugeVec, Histogram, HistogramVec, IntCounterVec,
+};
+
+pub static ORDERS_RECEIVED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "myco_orders_received_total",
+        "Open orders received for matching, per market and side",
+        &["market_id", "side"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static MATCHES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "myco_matches_total",
+        "Matches produced by the matching algorithm",
+        &["algorithm"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static MATCHED_ENERGY: Lazy<CounterVec> = Lazy::new(|| {
+    register_counter_vec!(
+        "myco_matched_energy_total",
+        "Energy of the matches produced by the matching algorithm",
+        &["algorithm"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static AVERAGE_TRADE_RATE: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "myco_average_trade_rate",
+        "Energy weighted average trade rate of the last matches",
+        &["algorithm"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static MATCHING_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "myco_matching_duration_seconds",
+        "Time spent by the matching algorithm on a market",
+        &["algorithm"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static RECONNECTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "myco_reconnects_total",
+        "Reconnections to Redis or to the node",
+        &["connection"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static EXTRINSICS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "myco_extrinsics_total",
+        "Extrinsics submitted to the node, per result",
+        &["extrinsic", "result"]
+    )
+    .expect("metric can be registered")
+});
+
+pub static ORDERBOOK_FETCH_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "myco_orderbook_fetch_duration_seconds",
+        "Time spent fetching the open orders from the orderbook service"
+    )
+    .expect("metric can be registered")
+});
+
+/// Counts the open orders of a market
+pub fn record_orders(market_id: &str, bids: usize, offers: usize) {
+    ORDERS_RECEIVED.with_label_values(&[market_id, "bid"]).inc_by(bids as u64);
+    ORDERS_RECEIVED.with_label_values(&[market_id, "offer"]).inc_by(offers as u64);
+}
+
+/// Records the matches produced by an algorithm on a market
+pub fn record_matches(algorithm: &str, matches: &[BidOfferMatch]) {
+    record_volumes(
+        algorithm,
+        matches
+            .iter()
+            .map(|bid_offer_match| (bid_offer_match.selected_energy, bid_offer_match.trade_rate))
+            .collect(),
+    );
+}
+
+/// Records the trades produced by an algorithm on a web3 market
+pub fn record_trades(algorithm: &str, trades: &[Trade]) {
+    record_volumes(
+        algorithm,
+        trades.iter().map(|trade| (trade.selected_energy, trade.energy_rate)).collect(),
+    );
+}
+
+fn record_volumes(algorithm: &str, volumes: Vec<(Energy, EnergyRate)>) {
+    if volumes.is_empty() {
+        return;
+    }
+    let energy: f64 = volumes.iter().map(|(energy, _)| energy.to_f64()).sum();
+    let value: f64 = volumes.iter().map(|(energy, rate)| energy.to_f64() * rate.to_f64()).sum();
+    MATCHES.with_label_values(&[algorithm]).inc_by(volumes.len() as u64);
+    MATCHED_ENERGY.with_label_values(&[algorithm]).inc_by(energy);
+    if energy > 0.0 {
+        AVERAGE_TRADE_RATE.with_label_values(&[algorithm]).set(value / energy);
+    }
+}
+
+/// Records the result of a submitted extrinsic
+pub fn record_extrinsic(extrinsic: &str, success: bool) {
+    let result = if success { "success" } else { "failure" };
+    EXTRINSICS.with_label_values(&[extrinsic, result]).inc();
+}
@@ -1,2 +1,16 @@
 mod cli;
-pub use cli::*;
\ No newline at end of file
+mod config;
+mod error;
+pub mod health;
+mod http;
+mod logging;
+pub mod metrics;
+mod offline;
+mod orders;
+pub use cli::*;
+pub use config::{redact_url, Config, ConfigLayer};
+pub use error::MycoError;
+pub use http::serve_http;
+pub use logging::{init_logging, LogFormat};
+pub use offline::{format_matches, read_offers_bids, OutputFormat};
+pub use orders::{format_orders, parse_order_hash, select_open_orders, OrderSide, OrderSpec};
\ No newline at end of file
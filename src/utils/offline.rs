@@ -0,0 +1,115 @@
+use crate::algorithms::VerifiedMatch;
+use crate::primitives::messages::{parse_message, OffersBidsResponse, RecommendationsMessage};
+use crate::utils::MycoError;
+use clap::ArgEnum;
+use std::io::Read;
+use std::path::Path;
+
+/// Output format of the `match` and `order list` commands
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Table,
+}
+
+/// Reads an offers-bids response payload from a file, or from stdin when
+/// no file or `-` is given
+pub fn read_offers_bids(input: Option<&Path>) -> Result<OffersBidsResponse, MycoError> {
+    let (source, payload) = match input {
+        Some(path) if path != Path::new("-") => {
+            let source = path.display().to_string();
+            let payload = std::fs::read_to_string(path).map_err(|error| MycoError::Input {
+                path: source.clone(),
+                source: error,
+            })?;
+            (source, payload)
+        }
+        _ => {
+            let mut payload = String::new();
+            std::io::stdin()
+                .read_to_string(&mut payload)
+                .map_err(|error| MycoError::Input {
+                    path: String::from("stdin"),
+                    source: error,
+                })?;
+            (String::from("stdin"), payload)
+        }
+    };
+    Ok(parse_message(&source, &payload)?)
+}
+
+/// Formats the verified matches as the recommendations message sent to
+/// the simulation, or as a table that also shows why a match is rejected
+pub fn format_matches(verified_matches: Vec<VerifiedMatch>, output: OutputFormat) -> Result<String, MycoError> {
+    match output {
+        OutputFormat::Json => {
+            let message = RecommendationsMessage {
+                recommended_matches: verified_matches
+                    .into_iter()
+                    .map(|verified_match| verified_match.recommendation)
+                    .collect(),
+            };
+            Ok(serde_json::to_string_pretty(&message)?)
+        }
+        OutputFormat::Table => Ok(format_table(&verified_matches)),
+    }
+}
+
+fn format_table(verified_matches: &[VerifiedMatch]) -> String {
+    let header = ["MARKET", "TIME SLOT", "BID", "OFFER", "ENERGY", "RATE", "VERIFICATION"];
+    let rows: Vec<Vec<String>> = verified_matches
+        .iter()
+        .map(|verified_match| {
+            let recommendation = &verified_match.recommendation;
+            vec![
+                recommendation.market_id.clone(),
+                recommendation
+                    .time_slot
+                    .map(|time_slot| time_slot.to_string())
+                    .unwrap_or_else(|| String::from("-")),
+                recommendation.bid.id.clone(),
+                recommendation.offer.id.clone(),
+                recommendation.selected_energy.to_string(),
+                recommendation.trade_rate.to_string(),
+                verified_match
+                    .rejection_reason
+                    .as_ref()
+                    .map_or_else(|| String::from("accepted"), |reason| format!("rejected: {}", reason)),
+            ]
+        })
+        .collect();
+
+    let mut table = render_table(&header, &rows);
+    let accepted = verified_matches.iter().filter(|verified_match| verified_match.is_accepted()).count();
+    table.push(format!(
+        "{} matches, {} accepted, {} rejected",
+        verified_matches.len(),
+        accepted,
+        verified_matches.len() - accepted
+    ));
+    table.join("\n")
+}
+
+/// Aligns the cells of the rows under the header, one line per row
+pub(crate) fn render_table(header: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut table = vec![format_row(header.to_vec())];
+    table.extend(rows.iter().map(|row| format_row(row.iter().map(String::as_str).collect())));
+    table
+}
@@ -0,0 +1,129 @@
+use crate::primitives::quantities::{Energy, EnergyRate};
+use crate::primitives::web3::{market_uuid, Bid, Offer, Order, OrderComponent, OrderSchema, OrderStatus};
+use crate::utils::offline::render_table;
+use crate::utils::{MycoError, OutputFormat};
+use clap::ArgEnum;
+use std::str::FromStr;
+use subxt::sp_core::{crypto::AccountId32, H256};
+
+/// Side of the order placed by the `order submit` command
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderSide {
+    Bid,
+    Offer,
+}
+
+/// Parameters of an order placed by a test bench or from the command line
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderSpec {
+    pub side: OrderSide,
+    /// Buyer of the bid or seller of the offer
+    pub account: AccountId32,
+    pub uuid: u8,
+    /// Market id of the TradesSettlement pallet
+    pub market: Option<u8>,
+    /// Unix timestamp of the time slot, in seconds
+    pub time_slot: u64,
+    pub creation_time: Option<u64>,
+    pub energy: Energy,
+    pub energy_rate: EnergyRate,
+    pub energy_type: Option<String>,
+    pub partners: Vec<AccountId32>,
+    pub priority: u32,
+}
+
+impl OrderSpec {
+    /// Builds the web3 order, with the quantities in the fixed-point units
+    /// of the chain and the market uuid that the trades are settled under
+    pub fn to_order(&self) -> Result<Order, MycoError> {
+        if self.energy.is_zero() {
+            return Err(MycoError::InvalidOrder(String::from("the energy of the order is zero")));
+        }
+        let component = OrderComponent {
+            energy: self.energy.to_chain_units()?,
+            energy_rate: self.energy_rate.to_chain_units()?,
+            pref_partners: (!self.partners.is_empty()).then(|| self.partners.clone()),
+            priority: self.priority,
+            energy_type: self.energy_type.clone().unwrap_or_default().into_bytes(),
+        };
+        let market_uuid = self.market.map(market_uuid);
+        Ok(match self.side {
+            OrderSide::Bid => Order::Bid(Bid {
+                buyer: self.account.clone(),
+                uuid: self.uuid,
+                market_uuid,
+                time_slot: self.time_slot,
+                creation_time: self.creation_time,
+                attributes: Vec::new(),
+                bid_component: component,
+            }),
+            OrderSide::Offer => Order::Offer(Offer {
+                seller: self.account.clone(),
+                uuid: self.uuid,
+                market_uuid,
+                time_slot: self.time_slot,
+                creation_time: self.creation_time,
+                attributes: Vec::new(),
+                offer_component: component,
+            }),
+        })
+    }
+}
+
+/// Parses the hash of an order, with or without its 0x prefix
+pub fn parse_order_hash(hash: &str) -> Result<H256, MycoError> {
+    H256::from_str(hash)
+        .map_err(|error| MycoError::InvalidOrder(format!("invalid order hash {}: {}", hash, error)))
+}
+
+/// Picks the open orders with the given hashes, which the removal by proxy takes in full
+pub fn select_open_orders(orders: Vec<OrderSchema>, hashes: &[H256]) -> Result<Vec<Order>, MycoError> {
+    hashes
+        .iter()
+        .map(|hash| match orders.iter().find(|order| order._id == *hash) {
+            Some(order) if order.status == OrderStatus::Open => Ok(order.order.clone()),
+            Some(order) => Err(MycoError::InvalidOrder(format!(
+                "order {:?} is {:?}, only open orders can be deleted",
+                hash, order.status
+            ))),
+            None => Err(MycoError::InvalidOrder(format!("order {:?} is not in the orderbook", hash))),
+        })
+        .collect()
+}
+
+/// Formats the orders of the orderbook service as JSON or as a table
+pub fn format_orders(orders: &[OrderSchema], output: OutputFormat) -> Result<String, MycoError> {
+    match output {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(orders)?),
+        OutputFormat::Table => Ok(format_table(orders)),
+    }
+}
+
+fn format_table(orders: &[OrderSchema]) -> String {
+    let header = ["HASH", "TYPE", "ACCOUNT", "MARKET", "TIME SLOT", "ENERGY", "RATE", "STATUS"];
+    let rows: Vec<Vec<String>> = orders
+        .iter()
+        .map(|order| {
+            let (side, market_uuid, time_slot, component) = match &order.order {
+                Order::Bid(bid) => ("bid", &bid.market_uuid, bid.time_slot, &bid.bid_component),
+                Order::Offer(offer) => ("offer", &offer.market_uuid, offer.time_slot, &offer.offer_component),
+            };
+            vec![
+                format!("{:?}", order._id),
+                side.to_string(),
+                order.order.account().to_string(),
+                market_uuid
+                    .as_ref()
+                    .map_or_else(|| String::from("-"), |market| String::from_utf8_lossy(market).into_owned()),
+                time_slot.to_string(),
+                Energy::from_chain_units(component.energy).to_string(),
+                EnergyRate::from_chain_units(component.energy_rate).to_string(),
+                format!("{:?}", order.status),
+            ]
+        })
+        .collect();
+
+    let mut table = render_table(&header, &rows);
+    table.push(format!("{} orders", orders.len()));
+    table.join("\n")
+}
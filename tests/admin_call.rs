@@ -0,0 +1,55 @@
+use myco_client_rust::connectors::AdminCall;
+use std::str::FromStr;
+use subxt::sp_core::crypto::AccountId32;
+
+// The well-known development accounts of Substrate
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+const ALICE_HEX: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+const BOB_HEX: &str = "8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48";
+
+// Indices of metadata.scale: the Sudo pallet is 07 and its sudo call 00, the
+// GsyCollateral pallet is 08, followed by the index of its call
+const SUDO: &str = "0700";
+const GSY_COLLATERAL: &str = "08";
+
+fn account(address: &str) -> AccountId32 {
+    AccountId32::from_str(address).unwrap()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn wraps_the_root_calls_in_sudo() {
+    let root_calls = [
+        (AdminCall::RegisterUser(account(ALICE)), "03"),
+        (AdminCall::RegisterMycoOperator(account(ALICE)), "02"),
+        (AdminCall::RestartVault(account(ALICE)), "04"),
+        (AdminCall::ShutdownVault(account(ALICE)), "05"),
+    ];
+    for (call, index) in root_calls {
+        assert!(call.requires_root(), "{}", call);
+        assert_eq!(hex(&call.encoded_call()), format!("{}{}{}{}", SUDO, GSY_COLLATERAL, index, ALICE_HEX));
+        assert_eq!(call.to_string(), format!("sudo({}({}))", call.name(), ALICE));
+    }
+}
+
+#[test]
+fn submits_the_signed_calls_directly() {
+    // 1000 as a little-endian u128, the SCALE encoding of the collateral amounts
+    let amount = "e8030000000000000000000000000000";
+    let signed_calls = [
+        (AdminCall::RegisterProxyAccount(account(BOB)), format!("01{}", BOB_HEX)),
+        (AdminCall::UnregisterProxyAccount(account(BOB)), format!("06{}", BOB_HEX)),
+        (AdminCall::DepositCollateral(1_000), format!("00{}", amount)),
+        (AdminCall::WithdrawCollateral(1_000), format!("07{}", amount)),
+    ];
+    for (call, encoded) in signed_calls {
+        assert!(!call.requires_root(), "{}", call);
+        assert_eq!(hex(&call.encoded_call()), format!("{}{}", GSY_COLLATERAL, encoded));
+        assert!(!call.to_string().starts_with("sudo("));
+    }
+    assert_eq!(AdminCall::DepositCollateral(1_000).to_string(), "deposit_collateral(1000)");
+}
@@ -0,0 +1,64 @@
+use myco_client_rust::connectors::MatchingCadence;
+use std::time::Duration;
+
+fn cadence(value: &str) -> MatchingCadence {
+    value.parse().unwrap()
+}
+
+#[test]
+fn parses_cadences() {
+    assert_eq!(cadence("blocks:4"), MatchingCadence::EveryBlocks(4));
+    assert_eq!(cadence(" blocks: 1 "), MatchingCadence::EveryBlocks(1));
+    assert_eq!(cadence("new-order"), MatchingCadence::NewOrder);
+    assert_eq!(MatchingCadence::default(), MatchingCadence::EveryBlocks(4));
+}
+
+#[test]
+fn parses_intervals() {
+    assert_eq!(cadence("interval:30s"), MatchingCadence::Interval(Duration::from_secs(30)));
+    assert_eq!(cadence("interval:45"), MatchingCadence::Interval(Duration::from_secs(45)));
+    assert_eq!(cadence("interval:500ms"), MatchingCadence::Interval(Duration::from_millis(500)));
+    assert_eq!(cadence("interval:2m"), MatchingCadence::Interval(Duration::from_secs(120)));
+    assert_eq!(cadence("interval: 1 s"), MatchingCadence::Interval(Duration::from_secs(1)));
+}
+
+#[test]
+fn round_trips_through_display() {
+    for value in ["blocks:4", "interval:30s", "interval:500ms", "interval:2m", "new-order"] {
+        let parsed = cadence(value);
+        assert_eq!(cadence(&parsed.to_string()), parsed, "{}", value);
+    }
+    assert_eq!(MatchingCadence::EveryBlocks(12).to_string(), "blocks:12");
+    assert_eq!(MatchingCadence::Interval(Duration::from_secs(2)).to_string(), "interval:2000ms");
+    assert_eq!(MatchingCadence::NewOrder.to_string(), "new-order");
+}
+
+#[test]
+fn rejects_zero_blocks() {
+    assert!("blocks:0".parse::<MatchingCadence>().is_err());
+    assert!("blocks:-1".parse::<MatchingCadence>().is_err());
+    assert!("blocks".parse::<MatchingCadence>().is_err());
+}
+
+#[test]
+fn rejects_invalid_durations() {
+    for value in [
+        "interval:0s",
+        "interval:0ms",
+        "interval:",
+        "interval:soon",
+        "interval:-5s",
+        "interval:1.5s",
+        "interval:1h",
+        "interval:307445734561825861m",
+    ] {
+        assert!(value.parse::<MatchingCadence>().is_err(), "{}", value);
+    }
+}
+
+#[test]
+fn rejects_unknown_cadences() {
+    assert!("hourly".parse::<MatchingCadence>().is_err());
+    assert!("new-order:5".parse::<MatchingCadence>().is_err());
+    assert!("".parse::<MatchingCadence>().is_err());
+}
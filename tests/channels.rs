@@ -0,0 +1,80 @@
+use myco_client_rust::connectors::{Channel, CHANNEL_PREFIX};
+
+const CHANNELS: [Channel; 5] = [
+    Channel::OffersBids,
+    Channel::OffersBidsResponse,
+    Channel::Recommendations,
+    Channel::RecommendationsVerification,
+    Channel::Events,
+];
+
+fn parse(channel_name: &str) -> Option<(String, Channel)> {
+    Channel::parse(channel_name)
+}
+
+#[test]
+fn names_the_channels() {
+    assert_eq!(Channel::OffersBids.name("sim"), "external-myco/sim/offers-bids/");
+    assert_eq!(Channel::OffersBidsResponse.name("sim"), "external-myco/sim/offers-bids/response/");
+    assert_eq!(Channel::Recommendations.name("sim"), "external-myco/sim/recommendations/");
+    assert_eq!(
+        Channel::RecommendationsVerification.name("sim"),
+        "external-myco/sim/recommendations/verification/"
+    );
+    assert_eq!(Channel::Events.name("sim"), "external-myco/sim/events/");
+    assert_eq!(Channel::Events.pattern(), "external-myco/*/events/");
+}
+
+#[test]
+fn parses_the_names_of_every_channel() {
+    for channel in CHANNELS {
+        for simulation_id in ["sim", "5f7b2e5c-5d0e-4cc8-9f0c-1d1c7cf0f7a2", ""] {
+            assert_eq!(
+                parse(&channel.name(simulation_id)),
+                Some((simulation_id.to_string(), channel)),
+                "{:?} of {:?}",
+                channel,
+                simulation_id
+            );
+        }
+    }
+}
+
+#[test]
+fn parses_simulation_ids_containing_separators() {
+    for channel in CHANNELS {
+        for simulation_id in ["region/sim", "sim/offers-bids", "sim/recommendations", "sim/events/"] {
+            assert_eq!(parse(&channel.name(simulation_id)).unwrap().1, channel);
+        }
+        assert_eq!(parse(&channel.name("region/sim")).unwrap().0, "region/sim");
+    }
+    assert_eq!(
+        parse("external-myco/sim/recommendations/recommendations/"),
+        Some((String::from("sim/recommendations"), Channel::Recommendations))
+    );
+}
+
+#[test]
+fn accepts_names_without_trailing_separator() {
+    assert_eq!(
+        parse("external-myco/sim/offers-bids/response"),
+        Some((String::from("sim"), Channel::OffersBidsResponse))
+    );
+}
+
+#[test]
+fn rejects_unknown_channels() {
+    for channel_name in [
+        "external-myco/sim/unknown/",
+        "external-myco/sim/events/unknown/",
+        "external-myco/sim/offers-bids/responses/",
+        "external-myco/sim/verification/",
+        "external-myco/events/",
+        "external-myco-sim/events/",
+        "other/sim/events/",
+        CHANNEL_PREFIX,
+        "",
+    ] {
+        assert_eq!(parse(channel_name), None, "{}", channel_name);
+    }
+}
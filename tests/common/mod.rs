@@ -0,0 +1,60 @@
+//! Factories of the web2 orders shared by the tests
+// Each test crate uses only some of them
+#![allow(dead_code)]
+
+use myco_client_rust::primitives::quantities::{Energy, EnergyRate, Price};
+use myco_client_rust::primitives::web2::{Bid, BidOfferMatch, Offer};
+
+pub fn energy(value: &str) -> Energy {
+    value.parse().unwrap()
+}
+
+pub fn rate(value: &str) -> EnergyRate {
+    value.parse().unwrap()
+}
+
+/// Bid without requirements, the buyer is also its id and origin
+pub fn bid(id: &str, buyer: &str, energy: &str, energy_rate: &str) -> Bid {
+    Bid {
+        r#type: String::from("Bid"),
+        id: id.to_string(),
+        energy: self::energy(energy),
+        energy_rate: rate(energy_rate),
+        original_price: Price::ZERO,
+        attributes: None,
+        requirements: None,
+        buyer_origin: buyer.to_string(),
+        buyer_origin_id: buyer.to_string(),
+        buyer_id: buyer.to_string(),
+        buyer: buyer.to_string(),
+        time_slot: None,
+        creation_time: None,
+    }
+}
+
+/// Offer without requirements, the seller is also its id and origin
+pub fn offer(id: &str, seller: &str, energy: &str, energy_rate: &str) -> Offer {
+    Offer {
+        r#type: String::from("Offer"),
+        id: id.to_string(),
+        energy: self::energy(energy),
+        energy_rate: rate(energy_rate),
+        original_price: Price::ZERO,
+        attributes: None,
+        requirements: None,
+        seller_origin: seller.to_string(),
+        seller_origin_id: seller.to_string(),
+        seller_id: seller.to_string(),
+        seller: seller.to_string(),
+        time_slot: None,
+        creation_time: None,
+    }
+}
+
+/// (bid id, offer id, selected energy) of each match
+pub fn trades(matches: &[BidOfferMatch]) -> Vec<(&str, &str, Energy)> {
+    matches
+        .iter()
+        .map(|m| (m.bid.id.as_str(), m.offer.id.as_str(), m.selected_energy))
+        .collect()
+}
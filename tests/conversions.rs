@@ -0,0 +1,272 @@
+mod common;
+
+use chrono::NaiveDateTime;
+use myco_client_rust::algorithms::{MatchingAlgorithm, PayAsBidAlgorithm};
+use myco_client_rust::primitives::quantities::{Energy, EnergyRate, Price};
+use myco_client_rust::primitives::requirements::{BidRequirement, OfferRequirement, OrderAttributes};
+use myco_client_rust::primitives::{web2, web3};
+use myco_client_rust::utils::MycoError;
+use std::str::FromStr;
+use subxt::sp_core::crypto::AccountId32;
+
+// The well-known development accounts of Substrate
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+const TIME_SLOT: u64 = 1_640_995_200;
+const CREATION_TIME: u64 = 1_640_991_600;
+
+fn datetime(value: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").unwrap()
+}
+
+fn account(address: &str) -> AccountId32 {
+    AccountId32::from_str(address).unwrap()
+}
+
+fn web2_bid(id: &str) -> web2::Bid {
+    web2::Bid {
+        original_price: "45.375".parse().unwrap(),
+        attributes: Some(OrderAttributes { energy_type: Some(String::from("PV")) }),
+        requirements: Some(vec![BidRequirement {
+            trading_partners: Some(vec![BOB.to_string()]),
+            energy_type: Some(vec![String::from("PV")]),
+            ..Default::default()
+        }]),
+        buyer_origin: String::from("house-1"),
+        buyer_origin_id: String::from("house-1-id"),
+        time_slot: Some(datetime("2022-01-01T00:00:00")),
+        creation_time: Some(datetime("2021-12-31T23:00:00")),
+        ..common::bid(id, ALICE, "1.5", "30.25")
+    }
+}
+
+fn web2_offer(id: &str) -> web2::Offer {
+    web2::Offer {
+        original_price: "41".parse().unwrap(),
+        attributes: Some(OrderAttributes { energy_type: Some(String::from("Wind")) }),
+        requirements: Some(vec![OfferRequirement {
+            trading_partners: Some(vec![ALICE.to_string()]),
+        }]),
+        seller_origin: String::from("farm-1"),
+        time_slot: Some(datetime("2022-01-01T00:00:00")),
+        ..common::offer(id, BOB, "2", "20.5")
+    }
+}
+
+fn web2_matching_data() -> web2::MatchingData {
+    web2::MatchingData {
+        bids: vec![web2_bid("7"), web2_bid("bid-1")],
+        offers: vec![web2_offer("12"), web2_offer("offer-1")],
+        market_id: String::from("market"),
+    }
+}
+
+fn component(energy: u32, energy_rate: u32, partner: &str, energy_type: &[u8]) -> web3::OrderComponent {
+    web3::OrderComponent {
+        energy,
+        energy_rate,
+        pref_partners: Some(vec![account(partner)]),
+        priority: 0,
+        energy_type: energy_type.to_vec(),
+    }
+}
+
+fn web3_bid(uuid: u8, attributes: &[&str]) -> web3::Bid {
+    web3::Bid {
+        buyer: account(ALICE),
+        uuid,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: TIME_SLOT,
+        creation_time: Some(CREATION_TIME),
+        attributes: attributes.iter().map(|attribute| attribute.as_bytes().to_vec()).collect(),
+        bid_component: component(150_000, 3_025_000, BOB, b"PV"),
+    }
+}
+
+fn web3_offer(uuid: u8, attributes: &[&str]) -> web3::Offer {
+    web3::Offer {
+        seller: account(BOB),
+        uuid,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: TIME_SLOT,
+        creation_time: None,
+        attributes: attributes.iter().map(|attribute| attribute.as_bytes().to_vec()).collect(),
+        offer_component: component(200_000, 2_050_000, ALICE, b"Wind"),
+    }
+}
+
+fn web3_matching_data() -> web3::MatchingData {
+    let mut matching_data = web3::MatchingData::new(Some(b"market".to_vec()), TIME_SLOT);
+    // The attributes are kept sorted by key, like the conversion writes them
+    let bids = [
+        web3_bid(7, &["buyer_origin=house-1", "energy_type=PV", "original_price=45.375"]),
+        web3_bid(0, &["id=bid-1", "original_price=45.375"]),
+    ];
+    let offers = [
+        web3_offer(12, &["original_price=41", "seller_origin=farm-1"]),
+        web3_offer(0, &["id=offer-1"]),
+    ];
+    for bid in bids {
+        matching_data.bids.push((web3::Order::Bid(bid.clone()).hash(), bid));
+    }
+    for offer in offers {
+        matching_data.offers.push((web3::Order::Offer(offer.clone()).hash(), offer));
+    }
+    matching_data
+}
+
+fn assert_lossy<T: std::fmt::Debug>(result: Result<T, MycoError>) {
+    match result {
+        Err(MycoError::LossyConversion(_)) => {}
+        other => panic!("expected a lossy conversion error, got {:?}", other),
+    }
+}
+
+#[test]
+fn web2_round_trips_through_web3() {
+    let matching_data = web2_matching_data();
+    let web3_matching_data = web3::MatchingData::try_from(matching_data.clone()).unwrap();
+
+    let numeric = &web3_matching_data.bids[0].1;
+    assert_eq!(numeric.uuid, 7);
+    assert_eq!(numeric.bid_component.energy, 150_000);
+    assert_eq!(numeric.bid_component.energy_rate, 3_025_000);
+    assert_eq!(numeric.bid_component.pref_partners, Some(vec![account(BOB)]));
+    assert_eq!(numeric.bid_component.energy_type, b"PV".to_vec());
+    assert_eq!(numeric.time_slot, TIME_SLOT);
+    assert_eq!(numeric.creation_time, Some(CREATION_TIME));
+    let other = &web3_matching_data.offers[1].1;
+    assert_eq!(other.uuid, 0);
+    assert!(other.attributes.contains(&b"id=offer-1".to_vec()));
+
+    assert_eq!(web2::MatchingData::try_from(web3_matching_data).unwrap(), matching_data);
+}
+
+#[test]
+fn web3_round_trips_through_web2() {
+    let matching_data = web3_matching_data();
+    let web2_matching_data = web2::MatchingData::try_from(matching_data.clone()).unwrap();
+
+    let numeric = &web2_matching_data.bids[0];
+    assert_eq!(numeric.id, "7");
+    assert_eq!(numeric.original_price, Price::from_str("45.375").unwrap());
+    assert_eq!(numeric.buyer_origin, "house-1");
+    assert_eq!(numeric.buyer_id, ALICE);
+    assert_eq!(numeric.attributes, Some(OrderAttributes { energy_type: Some(String::from("PV")) }));
+    assert_eq!(web2_matching_data.bids[1].id, "bid-1");
+    assert_eq!(web2_matching_data.offers[1].id, "offer-1");
+    assert_eq!(web2_matching_data.offers[0].energy, Energy::from_str("2").unwrap());
+    assert_eq!(web2_matching_data.offers[0].energy_rate, EnergyRate::from_str("20.5").unwrap());
+
+    assert_eq!(web3::MatchingData::try_from(web2_matching_data).unwrap(), matching_data);
+}
+
+#[test]
+fn matches_round_trip_through_trades() {
+    let bid_offer_match = web2::BidOfferMatch {
+        market_id: String::from("market"),
+        time_slot: Some(datetime("2022-01-01T00:00:00")),
+        bid: web2_bid("7"),
+        selected_energy: "1.5".parse().unwrap(),
+        offer: web2_offer("offer-1"),
+        trade_rate: "25".parse().unwrap(),
+    };
+    let trade = web3::Trade::try_from(bid_offer_match.clone()).unwrap();
+    assert_eq!(trade.bid_hash, web3::Order::Bid(trade.bid.clone()).hash());
+    assert_eq!(web2::BidOfferMatch::try_from(trade).unwrap(), bid_offer_match);
+}
+
+#[test]
+fn matching_data_identifies_web2_orders_by_hash() {
+    // to_web2 is the checked conversion with the web3 hash as the order id
+    let matching_data = web3_matching_data();
+    let converted = web2::MatchingData::try_from(matching_data.clone()).unwrap();
+    let for_matching = matching_data.to_web2();
+
+    for ((bid, converted_bid), (hash, _)) in
+        for_matching.bids.iter().zip(&converted.bids).zip(&matching_data.bids)
+    {
+        assert_eq!(bid.id, format!("{:?}", hash));
+        assert_eq!(web2::Bid { id: converted_bid.id.clone(), ..bid.clone() }, *converted_bid);
+    }
+    for ((offer, converted_offer), (hash, _)) in
+        for_matching.offers.iter().zip(&converted.offers).zip(&matching_data.offers)
+    {
+        assert_eq!(offer.id, format!("{:?}", hash));
+        assert_eq!(web2::Offer { id: converted_offer.id.clone(), ..offer.clone() }, *converted_offer);
+    }
+}
+
+#[test]
+fn rejects_sub_second_time_slots() {
+    let mut matching_data = web2_matching_data();
+    for bid in matching_data.bids.iter_mut() {
+        bid.time_slot = Some(datetime("2022-01-01T00:00:00.5"));
+    }
+    for offer in matching_data.offers.iter_mut() {
+        offer.time_slot = Some(datetime("2022-01-01T00:00:00.5"));
+    }
+    assert_lossy(web3::MatchingData::try_from(matching_data));
+}
+
+#[test]
+fn rejects_energy_over_the_chain_limit() {
+    // The chain stores the raw fixed-point value in a u32, i.e. at most 42949.67295 kWh
+    let mut matching_data = web2_matching_data();
+    matching_data.bids[0].energy = "42949.67296".parse().unwrap();
+    assert_lossy(web3::MatchingData::try_from(matching_data));
+}
+
+#[test]
+fn rejects_several_requirements() {
+    let mut matching_data = web2_matching_data();
+    let requirement = BidRequirement {
+        trading_partners: Some(vec![BOB.to_string()]),
+        ..Default::default()
+    };
+    matching_data.bids[0].requirements = Some(vec![requirement.clone(), requirement]);
+    assert_lossy(web3::MatchingData::try_from(matching_data));
+}
+
+#[test]
+fn rejects_priorities() {
+    let mut matching_data = web3_matching_data();
+    matching_data.bids[0].1.bid_component.priority = 1;
+    assert_lossy(web2::MatchingData::try_from(matching_data.clone()));
+}
+
+#[test]
+fn matching_ignores_the_fields_web2_orders_lack() {
+    // The matching algorithms use neither the priority nor the unknown attributes
+    let mut matching_data = web3_matching_data();
+    matching_data.bids[0].1.bid_component.priority = 1;
+    matching_data.offers[0].1.attributes.push(b"grid_fee=2".to_vec());
+    assert_lossy(web2::MatchingData::try_from(matching_data.clone()));
+
+    let for_matching = matching_data.to_web2();
+    assert_eq!(for_matching.bids.len(), 2);
+    assert_eq!(for_matching.offers.len(), 2);
+}
+
+#[test]
+fn matching_leaves_out_only_the_orders_it_cannot_convert() {
+    let mut matching_data = web3::MatchingData::new(Some(b"market".to_vec()), TIME_SLOT);
+    let mut bid = web3_bid(1, &[]);
+    bid.bid_component.energy_type = Vec::new();
+    let mut invalid = web3_bid(2, &[]);
+    invalid.bid_component.energy_type = vec![0xff];
+    invalid.bid_component.energy_rate = 4_000_000;
+    let offer = web3_offer(3, &[]);
+    let bid_hash = web3::Order::Bid(bid.clone()).hash();
+    let offer_hash = web3::Order::Offer(offer.clone()).hash();
+    matching_data.bids.push((bid_hash, bid));
+    matching_data.bids.push((web3::Order::Bid(invalid.clone()).hash(), invalid));
+    matching_data.offers.push((offer_hash, offer));
+
+    assert_eq!(matching_data.to_web2().bids.len(), 1);
+    let trades = PayAsBidAlgorithm.match_web3(&matching_data);
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].bid_hash, trades[0].offer_hash), (bid_hash, offer_hash));
+    assert_eq!(trades[0].selected_energy, Energy::from_str("1.5").unwrap());
+}
@@ -0,0 +1,147 @@
+use myco_client_rust::primitives::messages::{
+    parse_message, OffersBidsResponse, RecommendationsMessage, SlotCompletion, TickEvent,
+};
+use myco_client_rust::primitives::quantities::{Energy, EnergyRate, Price};
+
+// Payloads in the format gsy-e publishes: float quantities straight from
+// its arithmetic, time slots without seconds and null optional fields
+
+const OFFERS_BIDS_RESPONSE: &str = r#"{
+    "bids_offers": {
+        "market-1": {
+            "2022-01-01T00:15": {
+                "bids": [{
+                    "type": "Bid",
+                    "id": "bid-1",
+                    "energy": 0.30000000000000004,
+                    "energy_rate": 30.333333333333332,
+                    "original_price": 9.1,
+                    "attributes": null,
+                    "requirements": [{"trading_partners": ["farm-1"], "energy_type": ["PV"]}],
+                    "buyer_origin": "house-1",
+                    "buyer_origin_id": "house-1-id",
+                    "buyer_id": "house-1-id",
+                    "buyer": "house-1",
+                    "time_slot": "2022-01-01T00:15",
+                    "creation_time": "2022-01-01T00:10:00"
+                }],
+                "offers": [{
+                    "type": "Offer",
+                    "id": "offer-1",
+                    "energy": 1,
+                    "energy_rate": 20.5,
+                    "original_price": 20.5,
+                    "attributes": {"energy_type": "PV"},
+                    "requirements": null,
+                    "seller_origin": "farm-1",
+                    "seller_origin_id": "farm-1-id",
+                    "seller_id": "farm-1-id",
+                    "seller": "farm-1",
+                    "time_slot": "2022-01-01T00:15",
+                    "creation_time": null
+                }]
+            }
+        }
+    }
+}"#;
+
+const RECOMMENDATIONS: &str = r#"{
+    "recommended_matches": [{
+        "market_id": "market-1",
+        "time_slot": "2022-01-01T00:15",
+        "bid": {
+            "type": "Bid", "id": "bid-1", "energy": 0.3, "energy_rate": 30, "original_price": 9,
+            "attributes": null, "requirements": null, "buyer_origin": "house-1",
+            "buyer_origin_id": "house-1-id", "buyer_id": "house-1-id", "buyer": "house-1",
+            "time_slot": "2022-01-01T00:15", "creation_time": null
+        },
+        "selected_energy": 0.1,
+        "offer": {
+            "type": "Offer", "id": "offer-1", "energy": 1, "energy_rate": 20, "original_price": 20,
+            "attributes": null, "requirements": null, "seller_origin": "farm-1",
+            "seller_origin_id": "farm-1-id", "seller_id": "farm-1-id", "seller": "farm-1",
+            "time_slot": "2022-01-01T00:15", "creation_time": null
+        },
+        "trade_rate": 25
+    }]
+}"#;
+
+const CHANNEL: &str = "external-myco/simulation-1/offers-bids/response/";
+
+fn offers_bids(payload: &str) -> OffersBidsResponse {
+    parse_message(CHANNEL, payload).unwrap()
+}
+
+#[test]
+fn parses_an_offers_bids_response() {
+    let response = offers_bids(OFFERS_BIDS_RESPONSE);
+    let orders = &response.bids_offers["market-1"]["2022-01-01T00:15"];
+    let bid = &orders.bids[0];
+    assert_eq!(bid.energy, "0.3".parse::<Energy>().unwrap());
+    assert_eq!(bid.energy_rate, "30.33333".parse::<EnergyRate>().unwrap());
+    assert_eq!(bid.original_price, "9.1".parse::<Price>().unwrap());
+    assert_eq!(bid.time_slot.unwrap().to_string(), "2022-01-01 00:15:00");
+    assert_eq!(orders.offers[0].attributes.as_ref().unwrap().energy_type.as_deref(), Some("PV"));
+    assert_eq!(orders.offers[0].creation_time, None);
+    assert_eq!(orders.market_id, None);
+}
+
+#[test]
+fn offers_bids_responses_round_trip() {
+    let response = offers_bids(OFFERS_BIDS_RESPONSE);
+    let json = serde_json::to_string(&response).unwrap();
+    assert_eq!(offers_bids(&json), response);
+
+    let recommendations: RecommendationsMessage = parse_message(CHANNEL, RECOMMENDATIONS).unwrap();
+    let json = serde_json::to_string(&recommendations).unwrap();
+    assert_eq!(parse_message::<RecommendationsMessage>(CHANNEL, &json).unwrap(), recommendations);
+    assert_eq!(recommendations.recommended_matches[0].selected_energy, "0.1".parse().unwrap());
+}
+
+#[test]
+fn ignores_unknown_fields() {
+    // Fields added by a newer gsy-e must not make the whole payload unreadable
+    let mut payload: serde_json::Value = serde_json::from_str(OFFERS_BIDS_RESPONSE).unwrap();
+    let time_slot = &mut payload["bids_offers"]["market-1"]["2022-01-01T00:15"];
+    time_slot["market_type"] = "spot".into();
+    time_slot["bids"][0]["grid_fee"] = 1.5.into();
+
+    let response = offers_bids(&payload.to_string());
+    assert_eq!(response, offers_bids(OFFERS_BIDS_RESPONSE));
+}
+
+#[test]
+fn rejects_malformed_payloads() {
+    let mut payload: serde_json::Value = serde_json::from_str(OFFERS_BIDS_RESPONSE).unwrap();
+    payload["bids_offers"]["market-1"]["2022-01-01T00:15"]["bids"][0]["energy"] = (-1).into();
+    let error = parse_message::<OffersBidsResponse>(CHANNEL, &payload.to_string()).unwrap_err();
+    assert_eq!(error.channel, CHANNEL);
+    assert_eq!(error.to_response().status, "error");
+
+    let mut payload: serde_json::Value = serde_json::from_str(OFFERS_BIDS_RESPONSE).unwrap();
+    payload["bids_offers"]["market-1"]["2022-01-01T00:15"]["offers"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("seller");
+    assert!(parse_message::<OffersBidsResponse>(CHANNEL, &payload.to_string()).is_err());
+    assert!(parse_message::<OffersBidsResponse>(CHANNEL, "{\"bids_offers\": []}").is_err());
+}
+
+#[test]
+fn parses_the_slot_completion_of_tick_events() {
+    let tick = |payload: &str| parse_message::<TickEvent>("external-myco/simulation-1/events/", payload);
+
+    let event = tick(r#"{"event": "tick", "slot_completion": "45%", "market_slot": "2022-01-01T00:15"}"#)
+        .unwrap();
+    assert_eq!(event.event.as_deref(), Some("tick"));
+    assert_eq!(event.slot_completion, Some(SlotCompletion(45)));
+    assert_eq!(event.market_slot.as_deref(), Some("2022-01-01T00:15"));
+    assert_eq!(event.market_id, None);
+
+    assert_eq!(tick(r#"{"slot_completion": "100%"}"#).unwrap().slot_completion, Some(SlotCompletion(100)));
+    assert_eq!(tick(r#"{"slot_completion": " 0% "}"#).unwrap().slot_completion, Some(SlotCompletion(0)));
+    assert_eq!(tick(r#"{"event": "finish"}"#).unwrap().slot_completion, None);
+    for invalid in [r#""101%""#, r#""-5%""#, r#""half""#, "45"] {
+        assert!(tick(&format!(r#"{{"slot_completion": {}}}"#, invalid)).is_err(), "{}", invalid);
+    }
+}
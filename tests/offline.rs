@@ -0,0 +1,60 @@
+use myco_client_rust::algorithms::{MatchVerifier, PayAsBidAlgorithm};
+use myco_client_rust::connectors::match_offers_bids;
+use myco_client_rust::primitives::messages::RecommendationsMessage;
+use myco_client_rust::utils::{format_matches, read_offers_bids, MycoError, OutputFormat};
+use std::path::Path;
+
+const OFFERS_BIDS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/offers_bids.json");
+
+/// Runs the sample payload through the offline matcher, like `myco_client_rust match`
+fn match_sample(format: OutputFormat) -> String {
+    let response = read_offers_bids(Some(Path::new(OFFERS_BIDS))).unwrap();
+    let mut verifier = MatchVerifier::new();
+    let matches = match_offers_bids(&response, &PayAsBidAlgorithm, &mut verifier);
+    format_matches(verifier.verify(&matches), format).unwrap()
+}
+
+#[test]
+fn prints_the_matches_as_a_table() {
+    // The offers are matched from the most expensive one, at the rate of the bid
+    let expected = [
+        "MARKET    TIME SLOT            BID    OFFER    ENERGY  RATE  VERIFICATION",
+        "market-1  2022-01-01 00:15:00  bid-1  offer-2  1       30    accepted",
+        "market-1  2022-01-01 00:15:00  bid-2  offer-1  1.5     22    accepted",
+        "2 matches, 2 accepted, 0 rejected",
+    ];
+    assert_eq!(match_sample(OutputFormat::Table), expected.join("\n"));
+}
+
+#[test]
+fn prints_the_matches_as_a_recommendations_message() {
+    let message: RecommendationsMessage = serde_json::from_str(&match_sample(OutputFormat::Json)).unwrap();
+    let matches: Vec<_> = message
+        .recommended_matches
+        .iter()
+        .map(|m| {
+            (m.market_id.as_str(), m.bid.id.as_str(), m.offer.id.as_str(), m.selected_energy.to_string())
+        })
+        .collect();
+    assert_eq!(
+        matches,
+        vec![
+            ("market-1", "bid-1", "offer-2", String::from("1")),
+            ("market-1", "bid-2", "offer-1", String::from("1.5")),
+        ]
+    );
+}
+
+#[test]
+fn reports_unreadable_inputs() {
+    let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing.json");
+    match read_offers_bids(Some(&missing)) {
+        Err(MycoError::Input { path, .. }) => assert_eq!(path, missing.display().to_string()),
+        other => panic!("expected an input error, got {:?}", other),
+    }
+
+    let malformed = std::env::temp_dir().join(format!("myco_offline_{}.json", std::process::id()));
+    std::fs::write(&malformed, "{\"bids_offers\": []}").unwrap();
+    assert!(matches!(read_offers_bids(Some(&malformed)), Err(MycoError::Message(_))));
+    std::fs::remove_file(malformed).unwrap();
+}
@@ -0,0 +1,177 @@
+use codec::Encode;
+use myco_client_rust::connectors::gsy_node::runtime_types::gsy_primitives::orders as chain_orders;
+use myco_client_rust::connectors::{load_signer, submit_orders, KeyScheme};
+use myco_client_rust::primitives::web3::{Bid, Offer, Order, OrderComponent, OrderSchema, OrderStatus};
+use std::str::FromStr;
+use subxt::sp_core::crypto::AccountId32;
+use subxt::sp_runtime::traits::{BlakeTwo256, Hash};
+
+// The well-known development accounts of Substrate
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+type ChainOrder = chain_orders::Order<AccountId32, u64>;
+
+fn account(address: &str) -> AccountId32 {
+    AccountId32::from_str(address).unwrap()
+}
+
+fn component(energy: u32, energy_rate: u32) -> OrderComponent {
+    OrderComponent {
+        energy,
+        energy_rate,
+        pref_partners: Some(vec![account(BOB)]),
+        priority: 0,
+        energy_type: b"PV".to_vec(),
+    }
+}
+
+fn bid(uuid: u8) -> Bid {
+    Bid {
+        buyer: account(ALICE),
+        uuid,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: 1_640_995_200,
+        creation_time: Some(1_640_991_600),
+        attributes: vec![b"id=bid-1".to_vec()],
+        bid_component: component(200_000, 3_000_000),
+    }
+}
+
+fn offer(uuid: u8) -> Offer {
+    Offer {
+        seller: account(BOB),
+        uuid,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: 1_640_995_200,
+        creation_time: None,
+        attributes: Vec::new(),
+        offer_component: component(150_000, 2_000_000),
+    }
+}
+
+fn chain_component(energy: u32, energy_rate: u32) -> chain_orders::OrderComponent<AccountId32> {
+    chain_orders::OrderComponent {
+        energy,
+        energy_rate,
+        pref_partners: Some(vec![account(BOB)]),
+        priority: 0,
+        energy_type: b"PV".to_vec(),
+    }
+}
+
+/// The NewOrderInserted events of the node report the BlakeTwo256 hash of
+/// the SCALE encoding of its `Order<AccountId, BlockNumber>`
+fn chain_hash(order: &ChainOrder) -> subxt::sp_core::H256 {
+    BlakeTwo256::hash_of(order)
+}
+
+#[test]
+fn bid_hash_matches_the_chain() {
+    let chain_bid = ChainOrder::Bid(chain_orders::Bid {
+        buyer: account(ALICE),
+        uuid: 7,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: 1_640_995_200,
+        creation_time: Some(1_640_991_600),
+        attributes: vec![b"id=bid-1".to_vec()],
+        bid_component: chain_component(200_000, 3_000_000),
+    });
+    let order = Order::Bid(bid(7));
+    assert_eq!(order.encode(), chain_bid.encode());
+    assert_eq!(order.hash(), chain_hash(&chain_bid));
+}
+
+#[test]
+fn offer_hash_matches_the_chain() {
+    let chain_offer = ChainOrder::Offer(chain_orders::Offer {
+        seller: account(BOB),
+        uuid: 255,
+        market_uuid: Some(b"market".to_vec()),
+        time_slot: 1_640_995_200,
+        creation_time: None,
+        attributes: Vec::new(),
+        offer_component: chain_component(150_000, 2_000_000),
+    });
+    let order = Order::Offer(offer(255));
+    assert_eq!(order.encode(), chain_offer.encode());
+    assert_eq!(order.hash(), chain_hash(&chain_offer));
+}
+
+// Reference SCALE encodings and hashes of bid(7) and offer(255). They were
+// computed independently of this crate: the bytes were encoded by hand
+// following the type layout of metadata.scale, and hashed with the
+// blake2b-256 of Python's hashlib. They are not captured from a node.
+const BID_ENCODING: &str = "00d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d0701186d61726b6574\
+    8099cf610000000001708bcf6100000000042069643d6269642d31400d0300c0c62d0001048eaf04151687736326c9fea17e\
+    25fc5287613693c912909cb226aa4794f26a4800000000085056";
+const BID_HASH: &str = "0x3141d5dd3a114647a230007fed12ac020ce3fe8344d70b2f5495314bb76eadbc";
+const OFFER_ENCODING: &str = "018eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48ff01186d61726b6574\
+    8099cf61000000000000f049020080841e0001048eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f2\
+    6a4800000000085056";
+const OFFER_HASH: &str = "0x202dc4fbaccc06a3840796186ecb955ec612da0544a6a06145b3500a79c6e5a4";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn hashes_match_the_reference_values() {
+    let bid = Order::Bid(bid(7));
+    assert_eq!(hex(&bid.encode()), BID_ENCODING);
+    assert_eq!(format!("{:?}", bid.hash()), BID_HASH);
+
+    let offer = Order::Offer(offer(255));
+    assert_eq!(hex(&offer.encode()), OFFER_ENCODING);
+    assert_eq!(format!("{:?}", offer.hash()), OFFER_HASH);
+}
+
+/// Checks the hash against a running GSY node and prints the encoded order
+/// and the hash the node reported, to be kept as a reference value above.
+/// Run with `cargo test --test order_hash -- --ignored --nocapture`, against
+/// `MYCO_TEST_NODE_URL` (default ws://127.0.0.1:9944) with //Alice registered
+/// as a user of the GsyCollateral pallet.
+#[tokio::test]
+#[ignore]
+async fn hash_matches_a_running_node() {
+    let node_url =
+        std::env::var("MYCO_TEST_NODE_URL").unwrap_or_else(|_| String::from("ws://127.0.0.1:9944"));
+    let signer = load_signer(Some("//Alice"), None, KeyScheme::Sr25519).unwrap();
+    let mut bid = bid(7);
+    // A new creation time, since the node does not insert an order twice
+    bid.creation_time = Some(chrono::Utc::now().timestamp() as u64);
+    let order = Order::Bid(bid);
+
+    let events = submit_orders(&node_url, &signer, std::slice::from_ref(&order), None)
+        .await
+        .unwrap();
+    let inserted = events
+        .iter()
+        .find(|event| event.name == "OrderbookWorker.NewOrderInserted")
+        .expect("the node reported no NewOrderInserted event");
+    println!("encoded order: {}", hex(&order.encode()));
+    println!("reported hash: {:?}", inserted.hash);
+    assert_eq!(inserted.hash, order.hash());
+}
+
+#[test]
+fn uuid_tells_identical_orders_apart() {
+    assert_ne!(Order::Bid(bid(1)).hash(), Order::Bid(bid(2)).hash());
+    let mut other_market = bid(1);
+    other_market.market_uuid = Some(b"other".to_vec());
+    assert_ne!(Order::Bid(bid(1)).hash(), Order::Bid(other_market).hash());
+}
+
+#[test]
+fn orderbook_json_round_trips() {
+    let order = OrderSchema::from(Order::Bid(bid(7)));
+    let json = serde_json::to_value(&order).unwrap();
+    assert_eq!(json["order"]["data"]["buyer"], ALICE);
+    assert_eq!(json["order"]["data"]["bid_component"]["pref_partners"][0], BOB);
+    assert_eq!(json["status"], "Open");
+
+    let parsed: OrderSchema = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, order);
+    assert_eq!(parsed._id, parsed.order.hash());
+    assert_eq!(parsed.status, OrderStatus::Open);
+}
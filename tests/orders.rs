@@ -0,0 +1,52 @@
+use myco_client_rust::primitives::web3::{market_uuid, settlement_market_id, Order};
+use myco_client_rust::utils::{MycoError, OrderSide, OrderSpec};
+use std::str::FromStr;
+use subxt::sp_core::crypto::AccountId32;
+
+// The well-known development account of Substrate
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+fn spec(market: Option<u8>) -> OrderSpec {
+    OrderSpec {
+        side: OrderSide::Bid,
+        account: AccountId32::from_str(ALICE).unwrap(),
+        uuid: 0,
+        market,
+        time_slot: 1_640_995_200,
+        creation_time: None,
+        energy: "1.5".parse().unwrap(),
+        energy_rate: "30".parse().unwrap(),
+        energy_type: None,
+        partners: Vec::new(),
+        priority: 0,
+    }
+}
+
+fn market_uuid_of(order: Order) -> Option<Vec<u8>> {
+    match order {
+        Order::Bid(bid) => bid.market_uuid,
+        Order::Offer(offer) => offer.market_uuid,
+    }
+}
+
+#[test]
+fn submitted_orders_can_be_settled_in_their_market() {
+    for market in [0, 1, u8::MAX] {
+        let market_uuid = market_uuid_of(spec(Some(market)).to_order().unwrap());
+        assert_eq!(market_uuid, Some(vec![market]));
+        assert_eq!(settlement_market_id(&market_uuid).unwrap(), market);
+    }
+    let market_uuid = market_uuid_of(spec(None).to_order().unwrap());
+    assert_eq!(market_uuid, None);
+    assert_eq!(settlement_market_id(&market_uuid).unwrap(), 0);
+}
+
+#[test]
+fn rejects_market_uuids_the_settlement_cannot_identify() {
+    assert_eq!(settlement_market_id(&Some(market_uuid(7))).unwrap(), 7);
+    assert_eq!(settlement_market_id(&Some(Vec::new())).unwrap(), 0);
+    assert!(matches!(
+        settlement_market_id(&Some(b"market-1".to_vec())),
+        Err(MycoError::InvalidOrder(_))
+    ));
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{energy, trades};
+use myco_client_rust::algorithms::PayAsBid;
+use myco_client_rust::primitives::quantities::{Energy, EnergyRate, Price};
+use myco_client_rust::primitives::web2::{Bid, MatchingData, Offer};
+
+// The quantities are given as floats, the way gsy-e sends them
+
+fn bid(id: &str, buyer: &str, energy: f64, energy_rate: f64) -> Bid {
+    Bid {
+        energy: Energy::from_f64(energy).unwrap(),
+        energy_rate: EnergyRate::from_f64(energy_rate).unwrap(),
+        original_price: Price::from_f64(energy * energy_rate).unwrap(),
+        ..common::bid(id, buyer, "0", "0")
+    }
+}
+
+fn offer(id: &str, seller: &str, energy: f64, energy_rate: f64) -> Offer {
+    Offer {
+        energy: Energy::from_f64(energy).unwrap(),
+        energy_rate: EnergyRate::from_f64(energy_rate).unwrap(),
+        original_price: Price::from_f64(energy * energy_rate).unwrap(),
+        ..common::offer(id, seller, "0", "0")
+    }
+}
+
+#[test]
+fn consumes_the_offer_energy_exactly() {
+    // In floats 0.1 + 0.2 - 0.1 - 0.2 is not zero, which would leave a residual for the last bid
+    let mut data = MatchingData {
+        bids: vec![
+            bid("bid-1", "alice", 0.1, 30.0),
+            bid("bid-2", "bob", 0.2, 25.0),
+            bid("bid-3", "dave", 0.1, 20.0),
+        ],
+        offers: vec![offer("offer-1", "carol", 0.1 + 0.2, 10.0)],
+        market_id: String::from("market"),
+    };
+
+    let matches = data.pay_as_bid();
+    assert_eq!(
+        trades(&matches),
+        vec![("bid-1", "offer-1", energy("0.1")), ("bid-2", "offer-1", energy("0.2"))]
+    );
+    assert_eq!(matches[0].trade_rate, "30".parse().unwrap());
+    assert_eq!(matches[0].bid.original_price, "3".parse().unwrap());
+}
+
+#[test]
+fn leaves_the_rounded_residual_to_the_next_bid() {
+    // A third of a kWh is kept as 0.33333, so three of them leave 0.00001
+    let mut data = MatchingData {
+        bids: vec![
+            bid("bid-1", "alice", 1.0 / 3.0, 30.0),
+            bid("bid-2", "bob", 1.0 / 3.0, 29.0),
+            bid("bid-3", "dave", 1.0 / 3.0, 28.0),
+            bid("bid-4", "erin", 1.0, 27.0),
+        ],
+        offers: vec![offer("offer-1", "carol", 1.0, 10.0)],
+        market_id: String::from("market"),
+    };
+
+    let matches = data.pay_as_bid();
+    assert_eq!(
+        trades(&matches),
+        vec![
+            ("bid-1", "offer-1", energy("0.33333")),
+            ("bid-2", "offer-1", energy("0.33333")),
+            ("bid-3", "offer-1", energy("0.33333")),
+            ("bid-4", "offer-1", energy("0.00001")),
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{bid, energy, offer, rate, trades};
+use myco_client_rust::algorithms::PayAsClear;
+use myco_client_rust::primitives::requirements::BidRequirement;
+use myco_client_rust::primitives::web2::{Bid, MatchingData, Offer};
+
+fn matching_data(bids: Vec<Bid>, offers: Vec<Offer>) -> MatchingData {
+    MatchingData { bids, offers, market_id: String::from("market") }
+}
+
+#[test]
+fn trades_at_a_uniform_price() {
+    let mut data = matching_data(
+        vec![bid("bid-1", "alice", "1", "30"), bid("bid-2", "bob", "1", "25")],
+        vec![offer("offer-1", "carol", "1", "10"), offer("offer-2", "dave", "1", "20")],
+    );
+
+    assert_eq!(data.clearing_rate(), Some(rate("20")));
+    let matches = data.pay_as_clear();
+    assert_eq!(
+        trades(&matches),
+        vec![("bid-1", "offer-1", energy("1")), ("bid-2", "offer-2", energy("1"))]
+    );
+    assert!(matches.iter().all(|m| m.trade_rate == rate("20")));
+}
+
+#[test]
+fn does_not_trade_without_crossing() {
+    let mut data = matching_data(
+        vec![bid("bid-1", "alice", "1", "10")],
+        vec![offer("offer-1", "carol", "1", "20")],
+    );
+
+    assert_eq!(data.clearing_rate(), None);
+    assert!(data.pay_as_clear().is_empty());
+}
+
+#[test]
+fn fills_orders_partially() {
+    let mut data = matching_data(
+        vec![bid("bid-1", "alice", "3", "30")],
+        vec![offer("offer-1", "carol", "1", "10"), offer("offer-2", "dave", "1.5", "20")],
+    );
+    let matches = data.pay_as_clear();
+    assert_eq!(
+        trades(&matches),
+        vec![("bid-1", "offer-1", energy("1")), ("bid-1", "offer-2", energy("1.5"))]
+    );
+    assert!(matches.iter().all(|m| m.trade_rate == rate("20")));
+
+    let mut data = matching_data(
+        vec![bid("bid-1", "alice", "1", "30"), bid("bid-2", "bob", "2", "25")],
+        vec![offer("offer-1", "carol", "2.5", "10")],
+    );
+    let matches = data.pay_as_clear();
+    assert_eq!(
+        trades(&matches),
+        vec![("bid-1", "offer-1", energy("1")), ("bid-2", "offer-1", energy("1.5"))]
+    );
+    assert!(matches.iter().all(|m| m.trade_rate == rate("10")));
+}
+
+#[test]
+fn excludes_self_trades_from_the_clearing_rate() {
+    // The cheapest offer belongs to the buyer, so it cannot set the clearing rate
+    let mut data = matching_data(
+        vec![bid("bid-1", "alice", "1", "30")],
+        vec![offer("offer-1", "alice", "1", "10"), offer("offer-2", "bob", "1", "20")],
+    );
+
+    assert_eq!(data.clearing_rate(), Some(rate("20")));
+    let matches = data.pay_as_clear();
+    assert_eq!(trades(&matches), vec![("bid-1", "offer-2", energy("1"))]);
+    assert_eq!(matches[0].trade_rate, rate("20"));
+}
+
+#[test]
+fn excludes_unsatisfied_requirements_from_the_clearing_rate() {
+    let mut restricted = bid("bid-1", "alice", "1", "30");
+    restricted.requirements = Some(vec![BidRequirement {
+        trading_partners: Some(vec![String::from("dave")]),
+        ..Default::default()
+    }]);
+    let mut data = matching_data(
+        vec![restricted],
+        vec![offer("offer-1", "carol", "1", "10"), offer("offer-2", "dave", "1", "20")],
+    );
+
+    assert_eq!(data.clearing_rate(), Some(rate("20")));
+    assert_eq!(trades(&data.pay_as_clear()), vec![("bid-1", "offer-2", energy("1"))]);
+}
@@ -0,0 +1,101 @@
+use myco_client_rust::primitives::quantities::{Energy, EnergyRate, QuantityError, DECIMALS};
+use std::str::FromStr;
+
+fn energy(value: &str) -> Energy {
+    Energy::from_str(value).unwrap()
+}
+
+#[test]
+fn parses_decimal_strings() {
+    assert_eq!(energy("12.5").raw(), 1_250_000);
+    assert_eq!(energy("0.00001").raw(), 1);
+    assert_eq!(energy(".5").raw(), 50_000);
+    assert_eq!(energy("3.").raw(), 300_000);
+    assert_eq!(energy(" 7 ").raw(), 700_000);
+    assert_eq!(EnergyRate::from_str("30.25").unwrap().raw(), 3_025_000);
+}
+
+#[test]
+fn rejects_invalid_strings() {
+    for value in ["", ".", "-1", "1e3", "1.2.3", "abc", "NaN"] {
+        assert!(
+            matches!(Energy::from_str(value), Err(QuantityError::InvalidValue(_))),
+            "{:?} should be invalid",
+            value
+        );
+    }
+}
+
+#[test]
+fn rejects_strings_with_more_than_the_kept_decimals() {
+    assert_eq!(DECIMALS, 5);
+    assert!(matches!(Energy::from_str("0.000001"), Err(QuantityError::TooPrecise(_))));
+    assert!(matches!(Energy::from_str("1.123456"), Err(QuantityError::TooPrecise(_))));
+}
+
+#[test]
+fn rounds_floats_to_the_kept_decimals() {
+    for value in [0.0, 0.1, 1.5, 12.34567, 42949.67295, 1e10] {
+        assert_eq!(Energy::from_f64(value).unwrap(), energy(&value.to_string()));
+    }
+    // The results of float arithmetic, as gsy-e sends them
+    assert_eq!(Energy::from_f64(0.1 + 0.2).unwrap(), energy("0.3"));
+    assert_eq!(Energy::from_f64(91.0 / 3.0).unwrap(), energy("30.33333"));
+    assert_eq!(Energy::from_f64(1.123456).unwrap(), energy("1.12346"));
+    assert_eq!(Energy::from_f64(0.000001).unwrap(), Energy::ZERO);
+    assert!(matches!(Energy::from_f64(-1.0), Err(QuantityError::InvalidValue(_))));
+    assert!(matches!(Energy::from_f64(f64::NAN), Err(QuantityError::InvalidValue(_))));
+    assert!(matches!(Energy::from_f64(f64::INFINITY), Err(QuantityError::InvalidValue(_))));
+}
+
+#[test]
+fn displays_without_trailing_zeros() {
+    assert_eq!(energy("12.50000").to_string(), "12.5");
+    assert_eq!(energy("3").to_string(), "3");
+    assert_eq!(energy("0.00001").to_string(), "0.00001");
+    assert_eq!(Energy::ZERO.to_string(), "0");
+    assert_eq!(energy("0.1").to_f64(), 0.1);
+}
+
+#[test]
+fn serialises_as_numbers() {
+    assert_eq!(serde_json::to_string(&energy("1.5")).unwrap(), "1.5");
+    assert_eq!(serde_json::to_string(&energy("3")).unwrap(), "3.0");
+
+    for value in ["0.1", "1.5", "12.34567", "42949.67295"] {
+        let json = serde_json::to_string(&energy(value)).unwrap();
+        assert_eq!(serde_json::from_str::<Energy>(&json).unwrap(), energy(value));
+    }
+}
+
+#[test]
+fn deserialises_numbers_and_strings() {
+    assert_eq!(serde_json::from_str::<Energy>("2").unwrap(), energy("2"));
+    assert_eq!(serde_json::from_str::<Energy>("2.5").unwrap(), energy("2.5"));
+    assert_eq!(serde_json::from_str::<Energy>("\"2.5\"").unwrap(), energy("2.5"));
+
+    assert_eq!(serde_json::from_str::<Energy>("0.30000000000000004").unwrap(), energy("0.3"));
+    assert_eq!(serde_json::from_str::<Energy>("30.333333333333332").unwrap(), energy("30.33333"));
+
+    assert!(serde_json::from_str::<Energy>("-2").is_err());
+    assert!(serde_json::from_str::<Energy>("\"0.123456\"").is_err());
+    assert!(serde_json::from_str::<Energy>("true").is_err());
+}
+
+#[test]
+fn rejects_overflows() {
+    assert!(matches!(Energy::from_str("184467440737095.51616"), Err(QuantityError::Overflow(_))));
+    assert!(matches!(Energy::from_f64(1e20), Err(QuantityError::Overflow(_))));
+    assert!(serde_json::from_str::<Energy>(&u64::MAX.to_string()).is_err());
+    assert_eq!(Energy::from_raw(u64::MAX).checked_add(Energy::from_raw(1)), None);
+    assert_eq!(Energy::ZERO.checked_sub(Energy::from_raw(1)), None);
+}
+
+#[test]
+fn converts_to_chain_units() {
+    // The chain stores the raw fixed-point value in a u32
+    assert_eq!(energy("1.5").to_chain_units().unwrap(), 150_000);
+    assert_eq!(Energy::from_chain_units(150_000), energy("1.5"));
+    assert_eq!(energy("42949.67295").to_chain_units().unwrap(), u32::MAX);
+    assert!(matches!(energy("42949.67296").to_chain_units(), Err(QuantityError::Overflow(_))));
+}
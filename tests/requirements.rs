@@ -0,0 +1,152 @@
+mod common;
+
+use common::rate;
+use myco_client_rust::algorithms::{trade_terms, TradeTerms};
+use myco_client_rust::primitives::quantities::Energy;
+use myco_client_rust::primitives::requirements::{BidRequirement, OfferRequirement, OrderAttributes};
+use myco_client_rust::primitives::web2::{Bid, Offer};
+
+// The owners have ids and origins distinct from their names
+
+fn bid(energy_rate: &str, requirements: Vec<BidRequirement>) -> Bid {
+    Bid {
+        requirements: Some(requirements),
+        buyer_origin: String::from("house-1"),
+        buyer_origin_id: String::from("house-1-id"),
+        buyer_id: String::from("alice-id"),
+        ..common::bid("bid-1", "alice", "2", energy_rate)
+    }
+}
+
+fn offer(energy_rate: &str, energy_type: Option<&str>) -> Offer {
+    Offer {
+        attributes: energy_type.map(|energy_type| OrderAttributes {
+            energy_type: Some(energy_type.to_string()),
+        }),
+        seller_origin: String::from("farm-1"),
+        seller_origin_id: String::from("farm-1-id"),
+        seller_id: String::from("bob-id"),
+        ..common::offer("offer-1", "bob", "3", energy_rate)
+    }
+}
+
+fn partners(partners: &[&str]) -> Option<Vec<String>> {
+    Some(partners.iter().map(|partner| partner.to_string()).collect())
+}
+
+fn energy_types(energy_types: &[&str]) -> Option<Vec<String>> {
+    partners(energy_types)
+}
+
+#[test]
+fn trades_at_the_bid_rate_without_requirements() {
+    let expected = Some(TradeTerms {
+        energy_rate: rate("30"),
+        requirement_index: None,
+        requirement_energy: None,
+    });
+    let mut without_requirements = bid("30", Vec::new());
+    assert_eq!(trade_terms(&without_requirements, &offer("20", None)), expected);
+    without_requirements.requirements = None;
+    assert_eq!(trade_terms(&without_requirements, &offer("20", None)), expected);
+    assert_eq!(trade_terms(&without_requirements, &offer("30", None)).unwrap().energy_rate, rate("30"));
+    assert_eq!(trade_terms(&without_requirements, &offer("30.00001", None)), None);
+}
+
+#[test]
+fn checks_the_trading_partners_of_the_bid() {
+    let requirement = |names: &[&str]| BidRequirement {
+        trading_partners: partners(names),
+        ..Default::default()
+    };
+    // The seller is identified by its name or by its id
+    assert!(trade_terms(&bid("30", vec![requirement(&["bob"])]), &offer("20", None)).is_some());
+    assert!(trade_terms(&bid("30", vec![requirement(&["carol", "bob-id"])]), &offer("20", None)).is_some());
+    assert!(trade_terms(&bid("30", vec![requirement(&["carol"])]), &offer("20", None)).is_none());
+    assert!(trade_terms(&bid("30", vec![requirement(&[])]), &offer("20", None)).is_none());
+}
+
+#[test]
+fn checks_the_trading_partners_of_the_offer() {
+    let with_partners = |names: &[&str]| {
+        let mut offer = offer("20", None);
+        offer.requirements = Some(vec![OfferRequirement { trading_partners: partners(names) }]);
+        offer
+    };
+    let bid = bid("30", Vec::new());
+    assert!(trade_terms(&bid, &with_partners(&["alice"])).is_some());
+    assert!(trade_terms(&bid, &with_partners(&["alice-id"])).is_some());
+    assert!(trade_terms(&bid, &with_partners(&["carol"])).is_none());
+
+    // The offer accepts the buyer if any of its requirements does
+    let mut offer = with_partners(&["carol"]);
+    offer.requirements.as_mut().unwrap().push(OfferRequirement { trading_partners: None });
+    assert!(trade_terms(&bid, &offer).is_some());
+}
+
+#[test]
+fn checks_the_energy_type() {
+    let bid = bid(
+        "30",
+        vec![BidRequirement {
+            energy_type: energy_types(&["PV", "Wind"]),
+            ..Default::default()
+        }],
+    );
+    assert!(trade_terms(&bid, &offer("20", Some("Wind"))).is_some());
+    assert!(trade_terms(&bid, &offer("20", Some("Coal"))).is_none());
+    // An offer without energy type does not satisfy an energy type requirement
+    assert!(trade_terms(&bid, &offer("20", None)).is_none());
+}
+
+#[test]
+fn trades_at_the_rate_of_the_requirement() {
+    let requirement = |energy_rate: &str| BidRequirement {
+        energy_rate: Some(rate(energy_rate)),
+        ..Default::default()
+    };
+    // The requirement rate replaces the bid rate, whether it is higher or lower
+    let terms = trade_terms(&bid("30", vec![requirement("35")]), &offer("32", None)).unwrap();
+    assert_eq!(terms.energy_rate, rate("35"));
+    let terms = trade_terms(&bid("30", vec![requirement("25")]), &offer("20", None)).unwrap();
+    assert_eq!(terms.energy_rate, rate("25"));
+    assert!(trade_terms(&bid("30", vec![requirement("25")]), &offer("27", None)).is_none());
+}
+
+#[test]
+fn uses_the_first_satisfied_requirement() {
+    let bid = bid(
+        "30",
+        vec![
+            BidRequirement {
+                trading_partners: partners(&["carol"]),
+                energy_rate: Some(rate("40")),
+                ..Default::default()
+            },
+            BidRequirement {
+                energy_type: energy_types(&["PV"]),
+                energy: Some("1.5".parse().unwrap()),
+                energy_rate: Some(rate("28")),
+                ..Default::default()
+            },
+            BidRequirement::default(),
+        ],
+    );
+
+    let terms = trade_terms(&bid, &offer("20", Some("PV"))).unwrap();
+    assert_eq!(
+        terms,
+        TradeTerms {
+            energy_rate: rate("28"),
+            requirement_index: Some(1),
+            requirement_energy: Some("1.5".parse::<Energy>().unwrap()),
+        }
+    );
+    assert_eq!(terms.requirement_key(&bid).as_deref(), Some("bid-1/requirements/1"));
+
+    // Above the rate of the second requirement, the last one applies with the bid rate
+    let terms = trade_terms(&bid, &offer("29", Some("PV"))).unwrap();
+    assert_eq!(terms.requirement_index, Some(2));
+    assert_eq!(terms.energy_rate, rate("30"));
+    assert_eq!(terms.requirement_energy, None);
+}
@@ -0,0 +1,99 @@
+use myco_client_rust::connectors::{load_signer, KeyScheme, MycoSigner};
+use std::path::PathBuf;
+use subxt::Signer;
+
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+/// Writes a keystore file in the temporary directory and returns its path
+fn keystore(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("myco_signer_{}_{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn account(signer: &MycoSigner) -> String {
+    signer.account_id().to_string()
+}
+
+#[test]
+fn loads_a_secret_uri() {
+    let signer = load_signer(Some("//Alice"), None, KeyScheme::Sr25519).unwrap();
+    assert!(matches!(signer, MycoSigner::Sr25519(_)));
+    assert_eq!(account(&signer), ALICE);
+}
+
+#[test]
+fn loads_a_keystore() {
+    // The node keystore stores the secret as a JSON string, a plain file is accepted too
+    let json = keystore("json", "\"//Alice\"");
+    let plain = keystore("plain", "//Alice\n");
+
+    let from_json = MycoSigner::from_keystore(&json, KeyScheme::Sr25519).unwrap();
+    let from_plain = load_signer(None, Some(&plain), KeyScheme::Sr25519).unwrap();
+    assert_eq!(account(&from_json), ALICE);
+    assert_eq!(account(&from_plain), ALICE);
+
+    std::fs::remove_file(json).unwrap();
+    std::fs::remove_file(plain).unwrap();
+}
+
+#[test]
+fn rejects_a_missing_keystore() {
+    let missing = std::env::temp_dir().join("myco_signer_missing_keystore");
+    assert!(MycoSigner::from_keystore(&missing, KeyScheme::Sr25519).is_err());
+}
+
+#[test]
+fn rejects_an_encrypted_keystore() {
+    // The JSON account exported by polkadot-js, its secret is encrypted
+    let export = keystore(
+        "polkadot_js",
+        concat!(
+            r#"{"encoded": "bXljbw==", "encoding": {"content": ["pkcs8", "sr25519"], "#,
+            r#""type": ["scrypt", "xsalsa20-poly1305"], "version": "3"}, "address": ""#,
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            r#""}"#,
+        ),
+    );
+    let error = MycoSigner::from_keystore(&export, KeyScheme::Sr25519).err().unwrap();
+    assert!(error.to_string().contains("polkadot-js"), "{}", error);
+    std::fs::remove_file(export).unwrap();
+}
+
+#[test]
+fn requires_exactly_one_key_source() {
+    let path = keystore("exclusive", "\"//Alice\"");
+
+    let both = load_signer(Some("//Alice"), Some(&path), KeyScheme::Sr25519);
+    assert!(both.is_err());
+    let neither = load_signer(None, None, KeyScheme::Sr25519);
+    assert!(neither.is_err());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn uses_the_key_scheme() {
+    let path = keystore("scheme", "\"//Alice\"");
+
+    let sr25519 = load_signer(None, Some(&path), KeyScheme::Sr25519).unwrap();
+    let ed25519 = load_signer(None, Some(&path), KeyScheme::Ed25519).unwrap();
+    assert!(matches!(sr25519, MycoSigner::Sr25519(_)));
+    assert!(matches!(ed25519, MycoSigner::Ed25519(_)));
+    // The same secret derives a different account with each scheme
+    assert_eq!(account(&sr25519), ALICE);
+    assert_ne!(account(&ed25519), ALICE);
+    let ed25519_secret = MycoSigner::from_secret_uri("//Alice", KeyScheme::Ed25519).unwrap();
+    assert_eq!(account(&ed25519), account(&ed25519_secret));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn parses_key_schemes() {
+    assert_eq!("sr25519".parse::<KeyScheme>().unwrap(), KeyScheme::Sr25519);
+    assert_eq!("Ed25519".parse::<KeyScheme>().unwrap(), KeyScheme::Ed25519);
+    assert_eq!(KeyScheme::Ed25519.to_string(), "ed25519");
+    assert!("ecdsa".parse::<KeyScheme>().is_err());
+    assert!(MycoSigner::from_secret_uri("not a secret", KeyScheme::Sr25519).is_err());
+}
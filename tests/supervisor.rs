@@ -0,0 +1,87 @@
+use myco_client_rust::connectors::{ReconnectSupervisor, RetryPolicy};
+use myco_client_rust::utils::MycoError;
+use std::time::Duration;
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(4),
+        max_attempts,
+    }
+}
+
+fn failure() -> Result<(), MycoError> {
+    Err(MycoError::Config(String::from("connection refused")))
+}
+
+/// Checks that the delays are between half and all of the expected backoff
+fn assert_jittered(policy: &RetryPolicy, failures: u32, backoff: Duration) {
+    for _ in 0..200 {
+        let delay = policy.delay(failures);
+        assert!(
+            delay >= backoff / 2 && delay <= backoff,
+            "delay {:?} after {} failures is not within half and all of {:?}",
+            delay,
+            failures,
+            backoff
+        );
+    }
+}
+
+#[test]
+fn doubles_the_delay_with_each_failure() {
+    let policy = RetryPolicy::default();
+    assert_jittered(&policy, 1, Duration::from_millis(500));
+    assert_jittered(&policy, 2, Duration::from_secs(1));
+    assert_jittered(&policy, 3, Duration::from_secs(2));
+    assert_jittered(&policy, 6, Duration::from_secs(16));
+    // A session that was connected resets the failures
+    assert_jittered(&policy, 0, Duration::from_millis(500));
+}
+
+#[test]
+fn caps_the_delay() {
+    let policy = RetryPolicy::default();
+    assert_jittered(&policy, 7, Duration::from_secs(30));
+    assert_jittered(&policy, 40, Duration::from_secs(30));
+    assert_jittered(&policy, u32::MAX, Duration::from_secs(30));
+}
+
+#[test]
+fn randomises_the_delay() {
+    let policy = RetryPolicy::default();
+    let delays: Vec<Duration> = (0..50).map(|_| policy.delay(4)).collect();
+    assert!(delays.iter().any(|delay| *delay != delays[0]));
+}
+
+#[tokio::test]
+async fn gives_up_after_the_max_attempts() {
+    let mut supervisor = ReconnectSupervisor::new("Redis", policy(3));
+    assert!(supervisor.reconnect(failure()).await.is_ok());
+    assert!(supervisor.reconnect(failure()).await.is_ok());
+    match supervisor.reconnect(failure()).await {
+        Err(MycoError::RetriesExhausted { name, attempts, source }) => {
+            assert_eq!(name, "Redis");
+            assert_eq!(attempts, 3);
+            assert!(matches!(*source, MycoError::Config(_)));
+        }
+        other => panic!("expected the retries to be exhausted, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn counts_consecutive_failures_only() {
+    let mut supervisor = ReconnectSupervisor::new("Redis", policy(2));
+    assert!(supervisor.reconnect(failure()).await.is_ok());
+    assert!(supervisor.reconnect(Ok(())).await.is_ok());
+    assert!(supervisor.reconnect(failure()).await.is_ok());
+    assert!(supervisor.reconnect(failure()).await.is_err());
+}
+
+#[tokio::test]
+async fn retries_forever_without_max_attempts() {
+    let mut supervisor = ReconnectSupervisor::new("node", policy(0));
+    for _ in 0..10 {
+        assert!(supervisor.reconnect(failure()).await.is_ok());
+    }
+}
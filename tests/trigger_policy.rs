@@ -0,0 +1,103 @@
+use myco_client_rust::connectors::{TickTrigger, TickTriggers, TriggerPolicy};
+use myco_client_rust::primitives::messages::SlotCompletion;
+
+/// Replays the slot completions of a tick sequence and returns the ticks that
+/// triggered a request. The response of each request is received on the ticks
+/// listed in `responses`.
+fn replay(policy: &str, completions: &[u8], responses: &[usize]) -> Vec<usize> {
+    let mut trigger = TickTrigger::new(policy.parse().unwrap());
+    let mut triggered = Vec::new();
+    for (tick, completion) in completions.iter().enumerate() {
+        if responses.contains(&tick) {
+            trigger.on_response();
+        }
+        if trigger.on_tick(SlotCompletion(*completion)) {
+            triggered.push(tick);
+        }
+    }
+    triggered
+}
+
+#[test]
+fn parses_policies() {
+    assert_eq!("completion:33".parse::<TriggerPolicy>().unwrap(), TriggerPolicy::AtCompletion(vec![33]));
+    assert_eq!(
+        "completion:75%,25,50".parse::<TriggerPolicy>().unwrap(),
+        TriggerPolicy::AtCompletion(vec![25, 50, 75])
+    );
+    assert_eq!("ticks:5".parse::<TriggerPolicy>().unwrap(), TriggerPolicy::EveryTicks(5));
+    assert_eq!(TriggerPolicy::AtCompletion(vec![25, 50]).to_string(), "completion:25,50");
+
+    assert!("completion:120".parse::<TriggerPolicy>().is_err());
+    assert!("ticks:0".parse::<TriggerPolicy>().is_err());
+    assert!("always".parse::<TriggerPolicy>().is_err());
+}
+
+#[test]
+fn triggers_once_per_slot() {
+    let completions = [10, 20, 30, 40, 50, 60, 70, 80, 90, 0, 10, 20, 30, 40, 50];
+    assert_eq!(replay("completion:33", &completions, &[4]), vec![3, 13]);
+}
+
+#[test]
+fn triggers_at_several_completions() {
+    let completions = [0, 20, 30, 50, 60, 80, 90];
+    assert_eq!(replay("completion:25,50,75", &completions, &[3, 4, 6]), vec![2, 3, 5]);
+}
+
+#[test]
+fn covers_the_completions_passed_between_two_ticks() {
+    let completions = [10, 60, 80];
+    assert_eq!(replay("completion:25,50,75", &completions, &[2]), vec![1, 2]);
+}
+
+#[test]
+fn triggers_every_n_ticks() {
+    let completions = [0, 10, 20, 30, 40, 50, 60];
+    assert_eq!(replay("ticks:2", &completions, &[2, 4, 6]), vec![1, 3, 5]);
+}
+
+#[test]
+fn postpones_triggers_while_a_request_is_outstanding() {
+    let completions = [20, 30, 50, 60, 70, 80];
+    assert_eq!(replay("completion:25,50", &completions, &[4]), vec![1, 4]);
+    assert_eq!(replay("ticks:1", &completions, &[3]), vec![0, 3]);
+}
+
+#[test]
+fn forgets_the_outstanding_request_of_the_previous_slot() {
+    let completions = [40, 50, 60, 10, 40];
+    assert_eq!(replay("completion:33", &completions, &[]), vec![0, 4]);
+}
+
+#[test]
+fn keeps_one_trigger_per_market_and_slot() {
+    let mut triggers = TickTriggers::new("completion:33".parse().unwrap());
+    let slot = Some("2022-01-01T00:00");
+
+    // The ticks of two markets interleave without resetting each other
+    assert!(!triggers.on_tick(Some("market-1"), slot, SlotCompletion(20)));
+    assert!(!triggers.on_tick(Some("market-2"), slot, SlotCompletion(10)));
+    assert!(triggers.on_tick(Some("market-1"), slot, SlotCompletion(40)));
+    assert!(triggers.on_tick(Some("market-2"), slot, SlotCompletion(35)));
+    assert!(!triggers.on_tick(Some("market-1"), slot, SlotCompletion(50)));
+    assert!(triggers.has_outstanding_request());
+
+    triggers.on_response();
+    assert!(!triggers.has_outstanding_request());
+    assert!(!triggers.on_tick(Some("market-1"), slot, SlotCompletion(60)));
+}
+
+#[test]
+fn starts_over_on_a_new_market_slot() {
+    let mut triggers = TickTriggers::new("completion:33".parse().unwrap());
+
+    assert!(triggers.on_tick(Some("market-1"), Some("2022-01-01T00:00"), SlotCompletion(40)));
+    // The next slot triggers even if the completion did not go backwards
+    assert!(triggers.on_tick(Some("market-1"), Some("2022-01-01T00:15"), SlotCompletion(40)));
+    assert!(!triggers.on_tick(Some("market-1"), Some("2022-01-01T00:15"), SlotCompletion(50)));
+
+    // Ticks without market or slot keep the behaviour of a single trigger
+    assert!(triggers.on_tick(None, None, SlotCompletion(40)));
+    assert!(!triggers.on_tick(None, None, SlotCompletion(50)));
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use chrono::NaiveDateTime;
+use common::{bid, offer};
+use myco_client_rust::algorithms::{MatchVerifier, RejectionReason};
+use myco_client_rust::primitives::requirements::BidRequirement;
+use myco_client_rust::primitives::web2::{Bid, BidOfferMatch, Offer};
+use std::slice;
+
+fn recommendation(bid: &Bid, offer: &Offer, selected_energy: &str, trade_rate: &str) -> BidOfferMatch {
+    BidOfferMatch {
+        market_id: String::from("market"),
+        time_slot: None,
+        bid: bid.clone(),
+        selected_energy: selected_energy.parse().unwrap(),
+        offer: offer.clone(),
+        trade_rate: trade_rate.parse().unwrap(),
+    }
+}
+
+fn verifier(bids: &[Bid], offers: &[Offer]) -> MatchVerifier {
+    let mut verifier = MatchVerifier::new();
+    verifier.update_market("market", bids, offers);
+    verifier
+}
+
+/// Verifies a single recommendation for the given orders
+fn rejection(bid: &Bid, offer: &Offer, recommendation: BidOfferMatch) -> Option<RejectionReason> {
+    let verified = verifier(slice::from_ref(bid), slice::from_ref(offer)).verify(&[recommendation]);
+    verified[0].rejection_reason.clone()
+}
+
+#[test]
+fn accepts_a_valid_match() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    let verified = verifier(slice::from_ref(&bid), slice::from_ref(&offer))
+        .verify(&[recommendation(&bid, &offer, "2", "25")]);
+    assert_eq!(verified.len(), 1);
+    assert!(verified[0].is_accepted());
+    assert_eq!(verified[0].recommendation, recommendation(&bid, &offer, "2", "25"));
+}
+
+#[test]
+fn rejects_unknown_bids() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    let unknown = common::bid("bid-2", "alice", "2", "30");
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&unknown, &offer, "1", "25")),
+        Some(RejectionReason::UnknownBid)
+    );
+}
+
+#[test]
+fn rejects_unknown_offers() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    let unknown = common::offer("offer-2", "bob", "3", "20");
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&bid, &unknown, "1", "25")),
+        Some(RejectionReason::UnknownOffer)
+    );
+}
+
+#[test]
+fn rejects_market_mismatches() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    let mut other_market = recommendation(&bid, &offer, "1", "25");
+    other_market.market_id = String::from("other-market");
+    assert_eq!(rejection(&bid, &offer, other_market), Some(RejectionReason::MarketMismatch));
+}
+
+#[test]
+fn rejects_time_slot_mismatches() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    let mut other_slot = recommendation(&bid, &offer, "1", "25");
+    other_slot.time_slot =
+        Some(NaiveDateTime::parse_from_str("2022-01-01T00:15", "%Y-%m-%dT%H:%M").unwrap());
+    assert_eq!(rejection(&bid, &offer, other_slot), Some(RejectionReason::TimeSlotMismatch));
+}
+
+#[test]
+fn rejects_offer_rates_above_the_bid_rate() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "35"));
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&bid, &offer, "1", "32")),
+        Some(RejectionReason::OfferRateAboveBidRate)
+    );
+}
+
+#[test]
+fn rejects_unsatisfied_requirements() {
+    let mut bid = bid("bid-1", "alice", "2", "30");
+    bid.requirements = Some(vec![BidRequirement {
+        trading_partners: Some(vec![String::from("carol")]),
+        ..Default::default()
+    }]);
+    let offer = offer("offer-1", "bob", "3", "20");
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&bid, &offer, "1", "25")),
+        Some(RejectionReason::RequirementsNotSatisfied)
+    );
+}
+
+#[test]
+fn rejects_trade_rates_out_of_range() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    for trade_rate in ["19.99999", "30.00001"] {
+        assert_eq!(
+            rejection(&bid, &offer, recommendation(&bid, &offer, "1", trade_rate)),
+            Some(RejectionReason::TradeRateOutOfRange)
+        );
+    }
+}
+
+#[test]
+fn rejects_zero_selected_energy() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&bid, &offer, "0", "25")),
+        Some(RejectionReason::ZeroSelectedEnergy)
+    );
+}
+
+#[test]
+fn rejects_selected_energy_above_the_bid_energy() {
+    let (bid, offer) = (bid("bid-1", "alice", "2", "30"), offer("offer-1", "bob", "3", "20"));
+    assert_eq!(
+        rejection(&bid, &offer, recommendation(&bid, &offer, "2.5", "25")),
+        Some(RejectionReason::BidEnergyExceeded)
+    );
+}
+
+#[test]
+fn rejects_selected_energy_above_the_offer_energy() {
+    // The accepted matches consume the energy of their orders, the rejected ones do not
+    let (first, second) = (bid("bid-1", "alice", "2", "30"), bid("bid-2", "alice", "2", "30"));
+    let offer = offer("offer-1", "bob", "3", "20");
+    let verified = verifier(&[first.clone(), second.clone()], slice::from_ref(&offer)).verify(&[
+        recommendation(&first, &offer, "2", "25"),
+        recommendation(&second, &offer, "2", "25"),
+        recommendation(&second, &offer, "0", "25"),
+        recommendation(&second, &offer, "1", "25"),
+    ]);
+    let reasons: Vec<_> = verified.into_iter().map(|verified| verified.rejection_reason).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            None,
+            Some(RejectionReason::OfferEnergyExceeded),
+            Some(RejectionReason::ZeroSelectedEnergy),
+            None,
+        ]
+    );
+}